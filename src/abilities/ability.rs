@@ -0,0 +1,467 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::game_data::*;
+use crate::player::{window_to_world_position, ClimbingUp, Hanging, Player};
+use crate::status_effects::Stunned;
+
+// Most abilities the player can bring into a level
+pub const MAX_LOADOUT_SIZE: usize = 3;
+const ABILITY_HUD_ICON_SIZE: f32 = 33.;
+const ABILITY_HUD_FONT_SIZE: f32 = 33.;
+
+pub struct AbilityFrameworkPlugin;
+impl Plugin for AbilityFrameworkPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AbilityRegistry>()
+            .init_resource::<SelectedLoadout>()
+            .add_systems(Startup, select_default_loadout)
+            .add_systems(Update, (give_player_selected_loadout, spawn_ability_hud).chain())
+            .add_systems(
+                Update,
+                (ability_input_system, ability_hud_ui).run_if(in_state(GameState::PlayingLevel)),
+            )
+            .add_systems(
+                FixedUpdate,
+                recharge_ability_charges.run_if(in_state(GameState::PlayingLevel)),
+            );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct AbilityId(pub &'static str);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum AbilityInput {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+impl AbilityInput {
+    fn just_pressed(
+        &self,
+        keyboard_input: &ButtonInput<KeyCode>,
+        mouse_input: &ButtonInput<MouseButton>,
+    ) -> bool {
+        match *self {
+            AbilityInput::Key(key) => keyboard_input.just_pressed(key),
+            AbilityInput::Mouse(button) => mouse_input.just_pressed(button),
+        }
+    }
+
+    fn just_released(
+        &self,
+        keyboard_input: &ButtonInput<KeyCode>,
+        mouse_input: &ButtonInput<MouseButton>,
+    ) -> bool {
+        match *self {
+            AbilityInput::Key(key) => keyboard_input.just_released(key),
+            AbilityInput::Mouse(button) => mouse_input.just_released(button),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ActivateOn {
+    Press,
+    // Pressing starts aiming, releasing activates
+    Release,
+}
+
+pub struct AbilityDefinition {
+    pub name: &'static str,
+    // Asset path of the HUD icon, only the name is shown without one
+    pub icon: Option<&'static str>,
+    pub default_input: AbilityInput,
+    pub activate_on: ActivateOn,
+    pub max_charges: u8,
+    // Seconds for a single used charge to come back
+    pub recharge_time: f32,
+}
+
+/// Every ability that can be put in a loadout, in the order they were registered
+#[derive(Resource, Default)]
+pub struct AbilityRegistry(Vec<(AbilityId, AbilityDefinition)>);
+
+impl AbilityRegistry {
+    pub fn get(&self, ability: AbilityId) -> Option<&AbilityDefinition> {
+        self.0
+            .iter()
+            .find(|(id, _)| *id == ability)
+            .map(|(_, definition)| definition)
+    }
+
+    pub fn get_mut(&mut self, ability: AbilityId) -> Option<&mut AbilityDefinition> {
+        self.0
+            .iter_mut()
+            .find(|(id, _)| *id == ability)
+            .map(|(_, definition)| definition)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (AbilityId, &AbilityDefinition)> {
+        self.0.iter().map(|(id, definition)| (*id, definition))
+    }
+}
+
+pub trait RegisterAbility {
+    fn register_ability(
+        &mut self,
+        ability: AbilityId,
+        definition: AbilityDefinition,
+    ) -> &mut Self;
+}
+
+impl RegisterAbility for App {
+    fn register_ability(
+        &mut self,
+        ability: AbilityId,
+        definition: AbilityDefinition,
+    ) -> &mut Self {
+        self.init_resource::<AbilityRegistry>();
+        self.world_mut()
+            .resource_mut::<AbilityRegistry>()
+            .0
+            .push((ability, definition));
+        self
+    }
+}
+
+/// Abilities picked for the player before starting a level
+#[derive(Resource, Default)]
+pub struct SelectedLoadout(pub Vec<AbilityId>);
+
+impl SelectedLoadout {
+    /// Adds `ability` if there's room left, or removes it if it's already picked
+    pub fn toggle(&mut self, ability: AbilityId) {
+        if let Some(index) = self.0.iter().position(|&picked| picked == ability) {
+            self.0.remove(index);
+        } else if self.0.len() < MAX_LOADOUT_SIZE {
+            self.0.push(ability);
+        }
+    }
+}
+
+fn select_default_loadout(
+    mut selected_loadout: ResMut<SelectedLoadout>,
+    ability_registry: Res<AbilityRegistry>,
+) {
+    if selected_loadout.0.is_empty() {
+        selected_loadout.0 = ability_registry
+            .iter()
+            .map(|(ability, _)| ability)
+            .take(MAX_LOADOUT_SIZE)
+            .collect();
+    }
+}
+
+/// Abilities an entity activates through input, each in a slot bound to an input
+#[derive(Component)]
+pub struct AbilityLoadout {
+    pub slots: Vec<AbilitySlot>,
+}
+
+impl AbilityLoadout {
+    pub fn slot(&self, ability: AbilityId) -> Option<&AbilitySlot> {
+        self.slots.iter().find(|slot| slot.ability == ability)
+    }
+}
+
+pub struct AbilitySlot {
+    pub ability: AbilityId,
+    pub input: AbilityInput,
+    // Window position of the cursor when the input was pressed, while it's held down
+    pub press_window_position: Option<Vec2>,
+}
+
+#[derive(Clone, Copy)]
+struct ChargeState {
+    current: u8,
+    recharge_start: Option<f32>,
+}
+
+/// Charges left of every ability an entity has used, abilities that were never used are full
+#[derive(Component, Default)]
+pub struct AbilityCharges(HashMap<AbilityId, ChargeState>);
+
+impl AbilityCharges {
+    /// Uses up a charge of `ability`, returns false if there were none left
+    pub fn try_use(&mut self, ability: AbilityId, registry: &AbilityRegistry, now: f32) -> bool {
+        // Abilities that aren't registered aren't limited
+        let Some(definition) = registry.get(ability) else {
+            return true;
+        };
+        let charge_state = self.0.entry(ability).or_insert(ChargeState {
+            current: definition.max_charges,
+            recharge_start: None,
+        });
+        if charge_state.current == 0 {
+            return false;
+        }
+        charge_state.current -= 1;
+        if charge_state.recharge_start.is_none() {
+            charge_state.recharge_start = Some(now);
+        }
+        true
+    }
+
+    /// Brings all charges of `ability` back at once
+    pub fn refill(&mut self, ability: AbilityId) {
+        self.0.remove(&ability);
+    }
+
+    pub fn charges(&self, ability: AbilityId, registry: &AbilityRegistry) -> u8 {
+        match self.0.get(&ability) {
+            Some(charge_state) => charge_state.current,
+            None => registry
+                .get(ability)
+                .map_or(0, |definition| definition.max_charges),
+        }
+    }
+
+    /// Seconds until the next charge of `ability` comes back, if any are used
+    pub fn recharge_left(
+        &self,
+        ability: AbilityId,
+        registry: &AbilityRegistry,
+        now: f32,
+    ) -> Option<f32> {
+        let recharge_start = self.0.get(&ability)?.recharge_start?;
+        let definition = registry.get(ability)?;
+        Some((definition.recharge_time - (now - recharge_start)).max(0.))
+    }
+}
+
+fn recharge_ability_charges(
+    mut ability_charges_qy: Query<&mut AbilityCharges>,
+    ability_registry: Res<AbilityRegistry>,
+    time: Res<Time>,
+) {
+    for mut ability_charges in ability_charges_qy.iter_mut() {
+        ability_charges.0.retain(|&ability, charge_state| {
+            let Some(definition) = ability_registry.get(ability) else {
+                return false;
+            };
+            if let Some(recharge_start) = charge_state.recharge_start {
+                if time.elapsed_secs() - recharge_start >= definition.recharge_time {
+                    charge_state.current += 1;
+                    // Keep recharging until full
+                    charge_state.recharge_start = Some(time.elapsed_secs());
+                }
+            }
+            // Full abilities are the same as never used ones
+            charge_state.current < definition.max_charges
+        });
+    }
+}
+
+/// The input of an ability was used, the ability itself decides if it has the charges for it
+#[derive(EntityEvent)]
+pub struct ActivateAbility {
+    pub entity: Entity,
+    pub ability: AbilityId,
+    // None while the cursor is outside the window
+    pub cursor_world_position: Option<Vec2>,
+    pub cursor_window_position: Option<Vec2>,
+    // Where the cursor was when the input was pressed, for abilities activating on release
+    pub press_window_position: Option<Vec2>,
+}
+
+/// The input of an ability activating on press was let go
+#[derive(EntityEvent)]
+pub struct ReleaseAbility {
+    pub entity: Entity,
+    pub ability: AbilityId,
+}
+
+fn ability_input_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    mut loadout_qy: Query<
+        (
+            Entity,
+            &mut AbilityLoadout,
+            Has<Stunned>,
+            Has<Hanging>,
+            Has<ClimbingUp>,
+        ),
+        With<Player>,
+    >,
+    ability_registry: Res<AbilityRegistry>,
+    window_qy: Query<&Window>,
+    camera_transform_qy: Query<&Transform, With<Camera>>,
+    mut commands: Commands,
+) {
+    let (Ok(window), Ok(camera_transform)) = (window_qy.single(), camera_transform_qy.single())
+    else {
+        return;
+    };
+    // Inputs are still handled without a cursor, so letting go outside the window ends abilities
+    let cursor_window_position = window.cursor_position();
+    let cursor_world_position = cursor_window_position.map(|cursor_window_position| {
+        window_to_world_position(window, camera_transform, cursor_window_position)
+    });
+
+    for (entity, mut loadout, is_stunned, is_hanging, is_climbing_up) in loadout_qy.iter_mut() {
+        // Both hands are on the ledge
+        let can_activate = !is_stunned && !is_hanging && !is_climbing_up;
+        for slot in loadout.slots.iter_mut() {
+            let Some(definition) = ability_registry.get(slot.ability) else {
+                continue;
+            };
+            let pressed = slot.input.just_pressed(&keyboard_input, &mouse_input);
+            let released = slot.input.just_released(&keyboard_input, &mouse_input);
+            if pressed {
+                slot.press_window_position = cursor_window_position;
+            }
+            let activate = match definition.activate_on {
+                ActivateOn::Press => pressed,
+                ActivateOn::Release => released,
+            };
+            if activate && can_activate {
+                commands.trigger(ActivateAbility {
+                    entity,
+                    ability: slot.ability,
+                    cursor_world_position,
+                    cursor_window_position,
+                    press_window_position: slot.press_window_position,
+                });
+            } else if released && definition.activate_on == ActivateOn::Press {
+                commands.trigger(ReleaseAbility {
+                    entity,
+                    ability: slot.ability,
+                });
+            }
+            if released {
+                slot.press_window_position = None;
+            }
+        }
+    }
+}
+
+fn give_player_selected_loadout(
+    player_qy: Query<Entity, Added<Player>>,
+    selected_loadout: Res<SelectedLoadout>,
+    ability_registry: Res<AbilityRegistry>,
+    mut commands: Commands,
+) {
+    for player in player_qy.iter() {
+        let slots = selected_loadout
+            .0
+            .iter()
+            .filter_map(|&ability| {
+                ability_registry.get(ability).map(|definition| AbilitySlot {
+                    ability,
+                    input: definition.default_input,
+                    press_window_position: None,
+                })
+            })
+            .collect();
+        commands.entity(player).insert(AbilityLoadout { slots });
+    }
+}
+
+/// UI node the player's ability slots are shown in
+#[derive(Component)]
+pub struct AbilityHudRoot;
+
+#[derive(Component)]
+struct AbilitySlotUi(AbilityId);
+
+/// The HUD slot of an ability was spawned, abilities can add their own UI to it
+#[derive(EntityEvent)]
+pub struct AbilitySlotUiSpawned {
+    pub entity: Entity,
+    pub ability: AbilityId,
+}
+
+fn spawn_ability_hud(
+    hud_root_qy: Query<Entity, Added<AbilityHudRoot>>,
+    player_qy: Query<&AbilityLoadout, With<Player>>,
+    ability_registry: Res<AbilityRegistry>,
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+) {
+    let Ok(loadout) = player_qy.single() else {
+        return;
+    };
+    for hud_root in hud_root_qy.iter() {
+        for slot in loadout.slots.iter() {
+            let Some(definition) = ability_registry.get(slot.ability) else {
+                continue;
+            };
+            let slot_node = commands
+                .spawn((
+                    Node {
+                        padding: UiRect::all(Val::Px(10.)),
+                        column_gap: Val::Px(5.),
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ChildOf(hud_root),
+                ))
+                .id();
+            if let Some(icon) = definition.icon {
+                commands.spawn((
+                    ImageNode::new(asset_server.load(icon)),
+                    Node {
+                        width: Val::Px(ABILITY_HUD_ICON_SIZE),
+                        height: Val::Px(ABILITY_HUD_ICON_SIZE),
+                        ..default()
+                    },
+                    ChildOf(slot_node),
+                ));
+            }
+            commands.spawn((
+                AbilitySlotUi(slot.ability),
+                Text::new(""),
+                TextFont {
+                    font_size: ABILITY_HUD_FONT_SIZE,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                TextShadow::default(),
+                ChildOf(slot_node),
+            ));
+            commands.trigger(AbilitySlotUiSpawned {
+                entity: slot_node,
+                ability: slot.ability,
+            });
+        }
+    }
+}
+
+fn ability_hud_ui(
+    mut ui_qy: Query<(&mut Text, &AbilitySlotUi)>,
+    player_qy: Query<Option<&AbilityCharges>, With<Player>>,
+    ability_registry: Res<AbilityRegistry>,
+    time: Res<Time>,
+) {
+    let Ok(ability_charges_opt) = player_qy.single() else {
+        return;
+    };
+    for (mut text, slot_ui) in ui_qy.iter_mut() {
+        let Some(definition) = ability_registry.get(slot_ui.0) else {
+            continue;
+        };
+        let (charges, recharge_left) = match ability_charges_opt {
+            Some(ability_charges) => (
+                ability_charges.charges(slot_ui.0, &ability_registry),
+                ability_charges.recharge_left(slot_ui.0, &ability_registry, time.elapsed_secs()),
+            ),
+            None => (definition.max_charges, None),
+        };
+        // Single charge abilities just show whether they're ready
+        let mut status = if definition.max_charges == 1 && charges == 1 {
+            "Ready".to_string()
+        } else if definition.max_charges == 1 {
+            String::new()
+        } else {
+            format!("{}/{}", charges, definition.max_charges)
+        };
+        if let Some(recharge_left) = recharge_left {
+            status += &format!(" ({:.1}s)", recharge_left);
+        }
+        text.0 = format!("{}: {}", definition.name, status.trim());
+    }
+}
@@ -0,0 +1,107 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::abilities::ability::*;
+use crate::abilities::grapple::{EndGrapple, Grappling};
+use crate::game_data::*;
+use crate::player::Ground;
+
+const BLINK_MAX_RANGE: f32 = 400.;
+const BLINK_COOLDOWN: f32 = 2.;
+// Distance the destination is moved back towards the start each time it's inside ground
+const BLINK_BACKOFF_STEP: f32 = 10.;
+
+pub const BLINK_ABILITY: AbilityId = AbilityId("blink");
+
+pub struct BlinkPlugin;
+impl Plugin for BlinkPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_ability(
+            BLINK_ABILITY,
+            AbilityDefinition {
+                name: "Blink",
+                icon: None,
+                default_input: AbilityInput::Key(KeyCode::KeyR),
+                activate_on: ActivateOn::Press,
+                max_charges: 1,
+                recharge_time: BLINK_COOLDOWN,
+            },
+        )
+        .add_observer(activate_blink);
+    }
+}
+
+/// Can teleport a short distance towards the cursor
+#[derive(Component)]
+#[require(AbilityCharges)]
+pub struct CanBlink;
+
+/// Furthest point from `start` towards `target` where `collider` doesn't overlap any ground
+fn safe_blink_destination(
+    spatial_query: &SpatialQuery,
+    ground_qy: &Query<(), With<Ground>>,
+    collider: &Collider,
+    filter: &SpatialQueryFilter,
+    start: Vec2,
+    target: Vec2,
+) -> Vec2 {
+    let path = target - start;
+    let path_length = path.length();
+    let direction = path.normalize_or_zero();
+    let mut distance = path_length;
+    while distance > 0. {
+        let destination = start + direction * distance;
+        let overlaps_ground = spatial_query
+            .shape_intersections(collider, destination, 0., filter)
+            .into_iter()
+            .any(|entity| ground_qy.contains(entity));
+        if !overlaps_ground {
+            return destination;
+        }
+        distance -= BLINK_BACKOFF_STEP;
+    }
+    start
+}
+
+fn activate_blink(
+    activate_ability_event: On<ActivateAbility>,
+    mut blink_qy: Query<
+        (&mut Transform, &Collider, &mut AbilityCharges, Has<Grappling>),
+        With<CanBlink>,
+    >,
+    ground_qy: Query<(), With<Ground>>,
+    spatial_query: SpatialQuery,
+    ability_registry: Res<AbilityRegistry>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    if activate_ability_event.ability != BLINK_ABILITY {
+        return;
+    }
+    let Some(cursor_world_position) = activate_ability_event.cursor_world_position else {
+        return;
+    };
+    let entity = activate_ability_event.entity;
+    let Ok((mut transform, collider, mut ability_charges, is_grappling)) =
+        blink_qy.get_mut(entity)
+    else {
+        return;
+    };
+    let start = transform.translation.truncate();
+    let target = start + (cursor_world_position - start).clamp_length_max(BLINK_MAX_RANGE);
+    let filter = SpatialQueryFilter::from_excluded_entities([entity]).with_mask(GameLayer::Default);
+    let destination =
+        safe_blink_destination(&spatial_query, &ground_qy, collider, &filter, start, target);
+    // Nowhere to go, keep the charge
+    if destination == start {
+        return;
+    }
+    if !ability_charges.try_use(BLINK_ABILITY, &ability_registry, time.elapsed_secs()) {
+        return;
+    }
+    // The rope would snap the entity back
+    if is_grappling {
+        commands.trigger(EndGrapple { entity });
+    }
+    transform.translation = destination.extend(transform.translation.z);
+}
@@ -0,0 +1,261 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::abilities::ability::*;
+use crate::game_data::*;
+use crate::player::Player;
+
+// Gameplay timers (cooldowns, fuses, status effects) all read the virtual clock through
+// `Res<Time>`, so they slow down together with everything else. Physics steps in the fixed
+// schedule, which is driven by the virtual clock, so slowing `Time<Virtual>` slows it too.
+// The player's own movement, dashes, climbing and shoot cooldown are scaled by
+// `BulletTime::player_time_factor` instead, so they keep close to their real time speed.
+// Only the meter and the camera run on real time.
+
+const DEFAULT_BULLET_TIME_SCALE: f32 = 0.3;
+// Seconds of bullet time a full meter holds, in real time
+const DEFAULT_BULLET_TIME_METER_MAX: f32 = 3.;
+// Meter regained per real second while not in bullet time
+const DEFAULT_BULLET_TIME_METER_RECHARGE: f32 = 0.5;
+// Least meter needed to start bullet time
+const DEFAULT_BULLET_TIME_MIN_METER: f32 = 0.5;
+const BULLET_TIME_GREY_TINT_COLOR: Color = Color::srgba(0.5, 0.5, 0.5, 0.35);
+const BULLET_TIME_METER_WIDTH: f32 = 100.;
+const BULLET_TIME_METER_HEIGHT: f32 = 12.;
+
+pub const BULLET_TIME_ABILITY: AbilityId = AbilityId("bullet_time");
+
+pub struct BulletTimePlugin;
+impl Plugin for BulletTimePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BulletTimeConfig>()
+            .register_type::<BulletTimeConfig>()
+            .init_resource::<BulletTime>()
+            .register_ability(
+                BULLET_TIME_ABILITY,
+                AbilityDefinition {
+                    name: "Bullet time",
+                    icon: None,
+                    default_input: AbilityInput::Key(KeyCode::ShiftLeft),
+                    activate_on: ActivateOn::Press,
+                    // Limited by the meter instead
+                    max_charges: 1,
+                    recharge_time: 0.,
+                },
+            )
+            .add_observer(activate_bullet_time)
+            .add_observer(release_bullet_time)
+            .add_observer(end_bullet_time)
+            .add_observer(spawn_bullet_time_meter_ui)
+            .add_systems(
+                Update,
+                (update_bullet_time_meter, bullet_time_meter_ui)
+                    .run_if(in_state(GameState::PlayingLevel)),
+            )
+            .add_systems(
+                FixedUpdate,
+                compensate_player_gravity.run_if(in_state(GameState::PlayingLevel)),
+            )
+            .add_systems(OnExit(GameState::PlayingLevel), end_bullet_time_on_exit);
+    }
+}
+
+#[derive(Reflect, Resource)]
+#[reflect(Resource)]
+pub struct BulletTimeConfig {
+    // Speed of the world relative to normal while in bullet time
+    pub time_scale: f32,
+    pub meter_max: f32,
+    pub meter_recharge: f32,
+    pub min_meter: f32,
+}
+
+impl Default for BulletTimeConfig {
+    fn default() -> Self {
+        BulletTimeConfig {
+            time_scale: DEFAULT_BULLET_TIME_SCALE,
+            meter_max: DEFAULT_BULLET_TIME_METER_MAX,
+            meter_recharge: DEFAULT_BULLET_TIME_METER_RECHARGE,
+            min_meter: DEFAULT_BULLET_TIME_MIN_METER,
+        }
+    }
+}
+
+/// Whether the world is slowed down, and how much bullet time is left
+#[derive(Resource)]
+pub struct BulletTime {
+    // Time scale while active
+    active_scale: Option<f32>,
+    pub meter: f32,
+}
+
+impl Default for BulletTime {
+    fn default() -> Self {
+        BulletTime {
+            active_scale: None,
+            meter: DEFAULT_BULLET_TIME_METER_MAX,
+        }
+    }
+}
+
+impl BulletTime {
+    pub fn is_active(&self) -> bool {
+        self.active_scale.is_some()
+    }
+
+    /// How much faster than the world the player runs, so it moves at close to normal speed
+    pub fn player_time_factor(&self) -> f32 {
+        self.active_scale.map_or(1., |scale| 1. / scale)
+    }
+}
+
+/// Full screen grey tint shown while in bullet time
+#[derive(Component)]
+struct BulletTimeOverlay;
+
+fn activate_bullet_time(
+    activate_ability_event: On<ActivateAbility>,
+    mut bullet_time: ResMut<BulletTime>,
+    bullet_time_config: Res<BulletTimeConfig>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut player_velocity_qy: Query<&mut LinearVelocity, With<Player>>,
+    mut commands: Commands,
+) {
+    if activate_ability_event.ability != BULLET_TIME_ABILITY
+        || bullet_time.is_active()
+        || bullet_time.meter < bullet_time_config.min_meter
+    {
+        return;
+    }
+    let scale = bullet_time_config.time_scale;
+    bullet_time.active_scale = Some(scale);
+    virtual_time.set_relative_speed(scale);
+    // Keep the player's speed in real time the same
+    for mut velocity in player_velocity_qy.iter_mut() {
+        velocity.0 /= scale;
+    }
+    commands.spawn((
+        GameEntity::LevelEntity,
+        BulletTimeOverlay,
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        BackgroundColor(BULLET_TIME_GREY_TINT_COLOR),
+        // Don't block clicks on the UI below
+        Pickable::IGNORE,
+    ));
+}
+
+fn release_bullet_time(release_ability_event: On<ReleaseAbility>, mut commands: Commands) {
+    if release_ability_event.ability == BULLET_TIME_ABILITY {
+        commands.trigger(EndBulletTime);
+    }
+}
+
+#[derive(Event)]
+pub struct EndBulletTime;
+
+fn end_bullet_time(
+    _: On<EndBulletTime>,
+    mut bullet_time: ResMut<BulletTime>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut player_velocity_qy: Query<&mut LinearVelocity, With<Player>>,
+    overlay_qy: Query<Entity, With<BulletTimeOverlay>>,
+    mut commands: Commands,
+) {
+    let Some(scale) = bullet_time.active_scale.take() else {
+        return;
+    };
+    virtual_time.set_relative_speed(1.);
+    for mut velocity in player_velocity_qy.iter_mut() {
+        velocity.0 *= scale;
+    }
+    for overlay in overlay_qy.iter() {
+        commands.entity(overlay).despawn();
+    }
+}
+
+fn end_bullet_time_on_exit(mut commands: Commands) {
+    commands.trigger(EndBulletTime);
+}
+
+fn update_bullet_time_meter(
+    mut bullet_time: ResMut<BulletTime>,
+    bullet_time_config: Res<BulletTimeConfig>,
+    real_time: Res<Time<Real>>,
+    mut commands: Commands,
+) {
+    if bullet_time.is_active() {
+        bullet_time.meter = (bullet_time.meter - real_time.delta_secs()).max(0.);
+        if bullet_time.meter == 0. {
+            commands.trigger(EndBulletTime);
+        }
+    } else {
+        bullet_time.meter = (bullet_time.meter
+            + bullet_time_config.meter_recharge * real_time.delta_secs())
+        .min(bullet_time_config.meter_max);
+    }
+}
+
+/// Gravity only pulls as hard as the world's slowed clock allows, pull the player harder to make up for it
+fn compensate_player_gravity(
+    mut player_qy: Query<(&mut LinearVelocity, Option<&GravityScale>), With<Player>>,
+    bullet_time: Res<BulletTime>,
+    gravity: Res<Gravity>,
+    time: Res<Time>,
+) {
+    if !bullet_time.is_active() {
+        return;
+    }
+    let time_factor = bullet_time.player_time_factor();
+    for (mut velocity, gravity_scale_opt) in player_qy.iter_mut() {
+        let gravity_scale = gravity_scale_opt.map_or(1., |gravity_scale| gravity_scale.0);
+        velocity.0 +=
+            gravity.0 * gravity_scale * (time_factor * time_factor - 1.) * time.delta_secs();
+    }
+}
+
+#[derive(Component)]
+struct BulletTimeMeterUi;
+
+/// Meter bar next to the bullet time HUD slot
+fn spawn_bullet_time_meter_ui(
+    slot_ui_spawned_event: On<AbilitySlotUiSpawned>,
+    mut commands: Commands,
+) {
+    if slot_ui_spawned_event.ability != BULLET_TIME_ABILITY {
+        return;
+    }
+    commands.spawn((
+        Node {
+            width: Val::Px(BULLET_TIME_METER_WIDTH),
+            height: Val::Px(BULLET_TIME_METER_HEIGHT),
+            border: UiRect::all(Val::Px(2.)),
+            ..default()
+        },
+        BorderColor::all(Color::WHITE),
+        ChildOf(slot_ui_spawned_event.entity),
+        children![(
+            BulletTimeMeterUi,
+            Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.6, 0.8, 1.0)),
+        )],
+    ));
+}
+
+fn bullet_time_meter_ui(
+    mut meter_ui_qy: Query<&mut Node, With<BulletTimeMeterUi>>,
+    bullet_time: Res<BulletTime>,
+    bullet_time_config: Res<BulletTimeConfig>,
+) {
+    for mut node in meter_ui_qy.iter_mut() {
+        node.width = Val::Percent(bullet_time.meter / bullet_time_config.meter_max * 100.);
+    }
+}
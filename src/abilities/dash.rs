@@ -1,15 +1,48 @@
 use bevy::prelude::*;
 use avian2d::prelude::*;
+use crate::abilities::ability::*;
+use crate::abilities::bullet_time::BulletTime;
+use crate::abilities::ground_pound::GroundPounding;
 use crate::game_data::*;
+use crate::grounded::{GroundDetector, Landed};
+use crate::status_effects::*;
 
-const DASH_DAMAGE: f32= 5.;
+const DEFAULT_DASH_SPEED: f32 = 4000.;
+const DEFAULT_DASH_DURATION: f32 = 0.15;
+const DEFAULT_DASH_DAMAGE: f32 = 5.;
+const DEFAULT_DASH_EXIT_VELOCITY_RETENTION: f32 = 0.;
+const DEFAULT_DASH_INVULNERABILITY_TIME: f32 = 0.2;
+const DEFAULT_DASH_CHARGES: u8 = 2;
+// Seconds for a single used charge to come back
+const DEFAULT_DASH_RECHARGE_TIME: f32 = 1.5;
+pub const DASH_ABILITY: AbilityId = AbilityId("dash");
+const DASH_STUN: StatusEffect = StatusEffect {
+    kind: StatusEffectKind::Stun,
+    duration: 1.,
+    magnitude: 0.,
+};
 
 pub struct DashPlugin;
 impl Plugin for DashPlugin {
     fn build(&self, app: &mut App) {
         app
+            .init_resource::<DashConfig>()
+            .register_type::<DashConfig>()
+            .register_ability(
+                DASH_ABILITY,
+                AbilityDefinition {
+                    name: "Dash",
+                    icon: None,
+                    default_input: AbilityInput::Mouse(MouseButton::Right),
+                    activate_on: ActivateOn::Release,
+                    max_charges: DEFAULT_DASH_CHARGES,
+                    recharge_time: DEFAULT_DASH_RECHARGE_TIME,
+                },
+            )
+            .add_observer(activate_dash)
             .add_observer(recieve_dash_event)
             .add_observer(end_dash)
+            .add_observer(refill_dash_charges_on_landing)
             .add_systems(
                 FixedUpdate,
                 (dashing_system).run_if(in_state(GameState::PlayingLevel)),
@@ -21,9 +54,78 @@ impl Plugin for DashPlugin {
     }
 }
 
+#[derive(Reflect, Resource)]
+#[reflect(Resource)]
+pub struct DashConfig {
+    pub speed: f32,
+    pub duration: f32,
+    // Dealt to everything with health the dash runs into
+    pub damage: f32,
+    // Fraction of the dash velocity kept when the dash ends
+    pub exit_velocity_retention: f32,
+    // Seconds from the start of the dash the dasher can't be damaged by projectiles
+    pub invulnerability_time: f32,
+}
+
+impl Default for DashConfig {
+    fn default() -> Self {
+        DashConfig {
+            speed: DEFAULT_DASH_SPEED,
+            duration: DEFAULT_DASH_DURATION,
+            damage: DEFAULT_DASH_DAMAGE,
+            exit_velocity_retention: DEFAULT_DASH_EXIT_VELOCITY_RETENTION,
+            invulnerability_time: DEFAULT_DASH_INVULNERABILITY_TIME,
+        }
+    }
+}
+
+/// Can dash as long as it has dash charges left, enemies included
 #[derive(Component)]
+#[require(AbilityCharges, CollidingEntities, GroundDetector)]
 pub struct CanDash;
 
+/// Dash direction in world space from a mouse drag in window space
+pub fn dash_direction_from_drag(start_position: Vec2, end_position: Vec2) -> Option<Vec2> {
+    let mut direction = (end_position - start_position).try_normalize()?;
+    direction.y = -direction.y; // Invert Y axis because window Y cords go downwards
+    Some(direction)
+}
+
+fn activate_dash(
+    activate_ability_event: On<ActivateAbility>,
+    dash_config: Res<DashConfig>,
+    ground_pounding_qy: Query<(), With<GroundPounding>>,
+    bullet_time: Res<BulletTime>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    // Can't dash out of a ground pound
+    if activate_ability_event.ability != DASH_ABILITY
+        || ground_pounding_qy.contains(activate_ability_event.entity)
+    {
+        return;
+    }
+    // Dashes towards where the cursor was dragged since pressing
+    let (Some(start_position), Some(end_position)) = (
+        activate_ability_event.press_window_position,
+        activate_ability_event.cursor_window_position,
+    ) else {
+        return;
+    };
+    if let Some(direction) = dash_direction_from_drag(start_position, end_position) {
+        // Same dash in real time during bullet time
+        let time_factor = bullet_time.player_time_factor();
+        commands.trigger(DashEvent {
+            entity: activate_ability_event.entity,
+            direction,
+            duration: dash_config.duration / time_factor,
+            speed: dash_config.speed * time_factor,
+            invulnerability_time: dash_config.invulnerability_time / time_factor,
+            start_time: time.elapsed_secs(),
+        });
+    }
+}
+
 #[derive(Component)]
 pub struct Dashing {
     direction: Vec2,
@@ -31,6 +133,8 @@ pub struct Dashing {
     duration: f32,
     start_time: f32,
     started_moving: bool,
+    // Gravity scale to restore when the dash ends, None if there was no GravityScale
+    previous_gravity_scale: Option<f32>,
 }
 
 #[derive(EntityEvent)]
@@ -39,17 +143,38 @@ pub struct DashEvent {
     pub direction: Vec2,
     pub speed: f32,
     pub duration: f32,
+    pub invulnerability_time: f32,
     pub start_time: f32,
 }
 
 fn recieve_dash_event(
     dash_event: On<DashEvent>,
-    mut dash_entity_query: Query<(&mut LinearVelocity, Option<&mut GravityScale>), With<CanDash>>,
+    mut dash_entity_query: Query<
+        (
+            &mut LinearVelocity,
+            Option<&mut GravityScale>,
+            &mut AbilityCharges,
+            Option<&Dashing>,
+        ),
+        With<CanDash>,
+    >,
+    ability_registry: Res<AbilityRegistry>,
+    time: Res<Time>,
     mut commands: Commands,
 ) {
-    if let Ok((mut velocity, gravity_opt)) = dash_entity_query.get_mut(dash_event.entity) {
+    if let Ok((mut velocity, gravity_opt, mut ability_charges, dashing_opt)) =
+        dash_entity_query.get_mut(dash_event.entity)
+    {
+        if !ability_charges.try_use(DASH_ABILITY, &ability_registry, time.elapsed_secs()) {
+            return;
+        }
         // apply dash
         velocity.0 = dash_event.direction * dash_event.speed;
+        // Dashing again mid dash keeps the gravity scale from before the first dash
+        let previous_gravity_scale = match dashing_opt {
+            Some(dashing) => dashing.previous_gravity_scale,
+            None => gravity_opt.as_ref().map(|gravity_scale| gravity_scale.0),
+        };
         if let Some(mut gravityscale) = gravity_opt {
             *gravityscale = GravityScale(0.0);
         } else {
@@ -57,13 +182,19 @@ fn recieve_dash_event(
             commands.entity(dash_event.entity).insert(GravityScale(0.0));
         }
 
-        commands.entity(dash_event.entity).insert(Dashing {
-            direction: dash_event.direction,
-            speed: dash_event.speed,
-            duration: dash_event.duration,
-            start_time: dash_event.start_time,
-            started_moving: false,
-        });
+        commands.entity(dash_event.entity).insert((
+            Dashing {
+                direction: dash_event.direction,
+                speed: dash_event.speed,
+                duration: dash_event.duration,
+                start_time: dash_event.start_time,
+                started_moving: false,
+                previous_gravity_scale,
+            },
+            Invulnerable {
+                until: dash_event.start_time + dash_event.invulnerability_time,
+            },
+        ));
     } else {
         warn!(
             "Can't find entity {:?} with required components (Velocity, CanDash).",
@@ -78,12 +209,22 @@ struct EndDash {
 }
 fn end_dash(
     end_dash_event: On<EndDash>,
-    mut query: Query<(Entity, &mut GravityScale, &mut LinearVelocity), With<Dashing>>,
+    mut query: Query<(Entity, &Dashing, &mut LinearVelocity)>,
+    dash_config: Res<DashConfig>,
     mut commands: Commands,
 ) {
-    if let Ok((entity, mut gravity_scale, mut velocity)) = query.get_mut(end_dash_event.entity) {
-        velocity.0 = Vec2::ZERO;
-        *gravity_scale = GravityScale(1.);
+    if let Ok((entity, dashing, mut velocity)) = query.get_mut(end_dash_event.entity) {
+        velocity.0 = dashing.direction * dashing.speed * dash_config.exit_velocity_retention;
+        match dashing.previous_gravity_scale {
+            Some(previous_gravity_scale) => {
+                commands
+                    .entity(entity)
+                    .insert(GravityScale(previous_gravity_scale));
+            }
+            None => {
+                commands.entity(entity).remove::<GravityScale>();
+            }
+        }
         commands.entity(entity).remove::<Dashing>();
     }
 }
@@ -96,9 +237,20 @@ fn dashing_system(time: Res<Time>, query: Query<(Entity, &mut Dashing)>, mut com
         }
     }
 }
+/// Used dash charges all come back on landing
+fn refill_dash_charges_on_landing(
+    landed_event: On<Landed>,
+    mut ability_charges_qy: Query<&mut AbilityCharges, (With<CanDash>, Without<Dashing>)>,
+) {
+    if let Ok(mut ability_charges) = ability_charges_qy.get_mut(landed_event.entity) {
+        ability_charges.refill(DASH_ABILITY);
+    }
+}
+
 fn dash_collision_system(
     qy: Query<(Entity, &CollidingEntities, &mut Dashing)>,
-    mut damageable_qy: Query<&mut Health>,
+    mut damageable_qy: Query<&mut Health, Without<Invulnerable>>,
+    dash_config: Res<DashConfig>,
     mut commands: Commands,
 ) {
     for (entity, colliding_entities, mut dashing) in qy {
@@ -113,10 +265,11 @@ fn dash_collision_system(
             commands.trigger(EndDash { entity });
             for collision_entity in colliding_entities.0.iter() {
                 if let Ok(mut health) = damageable_qy.get_mut(*collision_entity) {
-                    health.0 -= DASH_DAMAGE;
-                    if health.0 < 0. {
-                        health.0 = 0.;
-                    }
+                    health.take_damage(dash_config.damage);
+                    commands.trigger(ApplyStatusEffect {
+                        entity: *collision_entity,
+                        effect: DASH_STUN,
+                    });
                 }
             }
         }
@@ -1,32 +1,79 @@
 use avian2d::prelude::*;
 use bevy::prelude::*;
+use crate::abilities::ability::*;
 use crate::enemy::Enemy;
 use crate::game_data::*;
+use crate::player::Ground;
+use crate::props::*;
+use crate::status_effects::*;
 
 // Tuning defaults
 const DEFAULT_GRAPPLING_HOOK_SPRING_FORCE: f32 = 100_000.0;
 const DEFAULT_GRAPPLING_HOOK_DAMPENING: f32 = 1_000_000.0;
 const GRAPPLING_HOOK_SIZE: f32 = 20.0;
-const GRAPPLE_ENEMY_PULL_FORCE: f32 = 1000000.;
+const DEFAULT_GRAPPLING_HOOK_SPEED: f32 = 3000.0;
+const DEFAULT_GRAPPLING_HOOK_MAX_RANGE: f32 = 1000.0;
+const DEFAULT_GRAPPLE_COOLDOWN: f32 = 0.5;
+const DEFAULT_ROPE_REEL_SPEED: f32 = 400.0;
+const DEFAULT_ROPE_SCROLL_REEL_STEP: f32 = 40.0;
+const DEFAULT_MIN_ROPE_LENGTH: f32 = 60.0;
+// How far outside a platform corner a rope pivot is placed, so the rope doesn't keep hitting it
+const ROPE_WRAP_MARGIN: f32 = 2.0;
+const DEFAULT_ENEMY_REEL_SPEED: f32 = 500.0;
+const DEFAULT_ENEMY_LEASH_LENGTH: f32 = 700.0;
+// Extra pull speed per unit the enemy is further away than the rope, and its cap
+const LEASH_CORRECTION_RATE: f32 = 8.0;
+const MAX_LEASH_CORRECTION_SPEED: f32 = 1000.0;
+// Distance between shooter and reeled in enemy at which the slam hits
+const GRAPPLE_SLAM_DISTANCE: f32 = 100.0;
+const GRAPPLE_SLAM_DAMAGE: f32 = 20.0;
+const GRAPPLE_SLAM_KNOCKBACK: f32 = 800.0;
+const GRAPPLE_SLAM_STUN: StatusEffect = StatusEffect {
+    kind: StatusEffectKind::Stun,
+    duration: 1.5,
+    magnitude: 0.,
+};
 
+pub const GRAPPLE_ABILITY: AbilityId = AbilityId("grapple");
 
 pub struct GrapplePlugin;
 impl Plugin for GrapplePlugin {
     fn build(&self, app: &mut App) {
         app
-            .init_resource::<GrappleKeybind>()
             .init_resource::<GrapplingHookConfig>()
+            .register_ability(
+                GRAPPLE_ABILITY,
+                AbilityDefinition {
+                    name: "Grapple",
+                    icon: Some("Grapplinghook.png"),
+                    default_input: AbilityInput::Key(KeyCode::Space),
+                    activate_on: ActivateOn::Press,
+                    // A single charge coming back after the cooldown, kept in sync with the config
+                    max_charges: 1,
+                    recharge_time: DEFAULT_GRAPPLE_COOLDOWN,
+                },
+            )
+            .add_observer(activate_grapple)
+            .add_observer(release_grapple)
             .add_observer(grapple_event_observer)
             .add_observer(hook_attachment_observer)
             .add_observer(end_grapple_event_observer)
+            .add_observer(reel_rope_event_observer)
+            .add_observer(grapple_slam_observer)
             
             .add_systems(
                 FixedUpdate,
                 (
                     
+                    release_grapple_on_lost_target,
+                    fixed_move_grappling_hook,
+                    fixed_wrap_rope,
+                    fixed_sync_rope_joint,
                     grappling_hook_swinging_spring_force_system_fixed,
                     damp_hook_spring_oscillation,
+                    fixed_reel_in_enemy,
                     hook_follow_enemy,
+                    hook_follow_prop,
                 )
                     .run_if(in_state(GameState::PlayingLevel)),
             )
@@ -35,7 +82,10 @@ impl Plugin for GrapplePlugin {
                 (draw_grapple_line,)
                     .run_if(in_state(GameState::PlayingLevel)),
             )
-            .add_systems(FixedLast, (fixed_attach_grappling_hook,));
+            .add_systems(
+                Update,
+                sync_grapple_cooldown.run_if(resource_changed::<GrapplingHookConfig>),
+            );
     }
 }
 
@@ -46,6 +96,7 @@ pub struct StartGrapple {
 }
 
 #[derive(Component)]
+#[require(AbilityCharges)]
 pub struct CanGrapple;
 
 #[derive(Component)]
@@ -57,6 +108,61 @@ pub struct Swinging {
     pub anchor: Vec2,
     pub rope_rest_length: f32,
     pub previous_distance_from_hook: Option<f32>,
+    // Corners the rope is wrapped around, ordered from the hook to the player
+    pub pivots: Vec<RopePivot>,
+    // Set when swinging on a physics joint instead of the spring
+    pub rope_joint: Option<RopeJoint>,
+}
+
+/// Kinematic body kept at the current anchor and the `DistanceJoint` tying the swinger to it
+#[derive(Clone, Copy)]
+pub struct RopeJoint {
+    anchor_body: Entity,
+    joint: Entity,
+    // Max length the joint was last created with
+    length: f32,
+}
+
+#[derive(Reflect, Clone, Copy, PartialEq, Default, Debug)]
+pub enum RopeMode {
+    // Hand-rolled spring pulling the swinger towards the anchor
+    #[default]
+    Spring,
+    // avian2d `DistanceJoint` that only limits the max rope length
+    Joint,
+}
+
+#[derive(Clone, Copy)]
+pub struct RopePivot {
+    pub position: Vec2,
+    // Side of the rope the swinger was on when it wrapped, the pivot unwraps when this flips
+    winding: f32,
+}
+
+impl Swinging {
+    /// The point currently swung around, the last pivot the rope wraps around or the hook itself
+    pub fn current_anchor(&self) -> Vec2 {
+        self.pivots.last().map_or(self.anchor, |pivot| pivot.position)
+    }
+
+    /// The point the rope comes from before reaching the current anchor
+    fn previous_anchor(&self) -> Vec2 {
+        match self.pivots.len() {
+            0 | 1 => self.anchor,
+            len => self.pivots[len - 2].position,
+        }
+    }
+
+    /// Rest length of the rope segment between the swinger and the current anchor
+    pub fn free_rope_rest_length(&self) -> f32 {
+        let mut wrapped_length = 0.;
+        let mut previous = self.anchor;
+        for pivot in &self.pivots {
+            wrapped_length += previous.distance(pivot.position);
+            previous = pivot.position;
+        }
+        (self.rope_rest_length - wrapped_length).max(0.)
+    }
 }
 
 #[derive(Component)]
@@ -66,15 +172,71 @@ pub struct PullingEnemy {
     pub rope_length: f32,
 }
 
-#[derive(Resource)]
-pub struct GrappleKeybind(pub KeyCode);
-impl Default for GrappleKeybind {
-    fn default() -> Self {
-        GrappleKeybind(KeyCode::Space)
+/// Hooked to a prop that gets yanked loose when the grapple is released
+#[derive(Component)]
+pub struct PullingProp {
+    pub hook_entity: Entity,
+    pub prop: Entity,
+}
+
+fn activate_grapple(
+    activate_ability_event: On<ActivateAbility>,
+    can_grapple_qy: Query<(), (With<CanGrapple>, Without<Grappling>)>,
+    mut commands: Commands,
+) {
+    // Aimed at the cursor, so there's nothing to aim at while it's outside the window
+    let Some(grapple_world_target) = activate_ability_event.cursor_world_position else {
+        return;
+    };
+    if activate_ability_event.ability == GRAPPLE_ABILITY
+        && can_grapple_qy.contains(activate_ability_event.entity)
+    {
+        commands.trigger(StartGrapple {
+            entity: activate_ability_event.entity,
+            grapple_world_target,
+        });
+    }
+}
+
+fn release_grapple(
+    release_ability_event: On<ReleaseAbility>,
+    grappling_qy: Query<(), With<Grappling>>,
+    mut commands: Commands,
+) {
+    if release_ability_event.ability == GRAPPLE_ABILITY
+        && grappling_qy.contains(release_ability_event.entity)
+    {
+        commands.trigger(EndGrapple {
+            entity: release_ability_event.entity,
+        });
     }
 }
 
-fn grapple_event_observer(grapple_start_event: On<StartGrapple>, mut commands: Commands, asset_server: Res<AssetServer>) {
+fn grapple_event_observer(
+    grapple_start_event: On<StartGrapple>,
+    mut shooter_qy: Query<(&Transform, Option<&mut AbilityCharges>)>,
+    grappling_hook_config: Res<GrapplingHookConfig>,
+    ability_registry: Res<AbilityRegistry>,
+    time: Res<Time>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    let Ok((shooter_transform, ability_charges_opt)) =
+        shooter_qy.get_mut(grapple_start_event.entity)
+    else {
+        return;
+    };
+    let shooter_pos = shooter_transform.translation.truncate();
+    let Some(direction) = (grapple_start_event.grapple_world_target - shooter_pos).try_normalize()
+    else {
+        return;
+    };
+    if let Some(mut ability_charges) = ability_charges_opt {
+        if !ability_charges.try_use(GRAPPLE_ABILITY, &ability_registry, time.elapsed_secs()) {
+            return;
+        }
+    }
+
     commands
         .entity(grapple_start_event.entity)
         .insert(Grappling); // marker only
@@ -82,7 +244,8 @@ fn grapple_event_observer(grapple_start_event: On<StartGrapple>, mut commands: C
     spawn_grapple(
         &mut commands,
         grapple_start_event.entity,
-        grapple_start_event.grapple_world_target.extend(0.),
+        shooter_transform.translation,
+        direction * grappling_hook_config.hook_speed,
         asset_server,
     );
 }
@@ -90,10 +253,15 @@ fn grapple_event_observer(grapple_start_event: On<StartGrapple>, mut commands: C
 pub struct GrapplingHook {
     shooter_entity: Entity,
     attached_to: Option<GrapplingHookAttachmentType>,
+    // Flying back to the shooter after missing or the grapple being released mid flight
+    retracting: bool,
+    // Where the hook sits on the prop it's attached to, in the prop's local space
+    prop_local_offset: Vec2,
 }
 #[derive(Clone, Copy)]
 enum GrapplingHookAttachmentType {
     Enemy(Entity),
+    Prop(Entity),
     World,
 }
 #[derive(EntityEvent)]
@@ -102,12 +270,20 @@ pub struct GrappleAttachedEvent {
     attachment_type: GrapplingHookAttachmentType,
 }
 
-fn spawn_grapple(commands: &mut Commands, shooter_entity: Entity, world_position: Vec3, asset_server: Res<AssetServer>) {
+fn spawn_grapple(
+    commands: &mut Commands,
+    shooter_entity: Entity,
+    world_position: Vec3,
+    velocity: Vec2,
+    asset_server: Res<AssetServer>,
+) {
     commands.spawn((
         GameEntity::LevelEntity,
         GrapplingHook {
             shooter_entity,
             attached_to: None,
+            retracting: false,
+            prop_local_offset: Vec2::ZERO,
         },
         Transform::from_translation(world_position),
         Sprite {
@@ -115,45 +291,91 @@ fn spawn_grapple(commands: &mut Commands, shooter_entity: Entity, world_position
             image: asset_server.load("Grapplinghook.png"),
             ..default()
         },
+        RigidBody::Kinematic,
+        LinearVelocity(velocity),
         Collider::rectangle(GRAPPLING_HOOK_SIZE, GRAPPLING_HOOK_SIZE),
-        CollidingEntities::default(),
+        Sensor,
     ));
 }
 
-fn fixed_attach_grappling_hook(
-    mut hook_qy: Query<(&mut GrapplingHook, &CollidingEntities, &Transform)>,
-    enemy_qy: Query<Entity, With<Enemy>>,
+/// Raycasts ahead of each flying hook for the distance it will travel this step, so it can't
+/// tunnel through thin platforms. Attaches to the first `Ground` or `Enemy` in the way and starts
+/// retracting once it has flown further than the max range.
+fn fixed_move_grappling_hook(
+    spatial_query: SpatialQuery,
+    mut hook_qy: Query<(Entity, &mut GrapplingHook, &mut Transform, &mut LinearVelocity)>,
+    shooter_transform_qy: Query<&Transform, Without<GrapplingHook>>,
+    ground_qy: Query<(), With<Ground>>,
+    enemy_qy: Query<(), With<Enemy>>,
+    prop_qy: Query<&GlobalTransform, With<Prop>>,
+    grappling_hook_config: Res<GrapplingHookConfig>,
+    time: Res<Time>,
     mut commands: Commands,
 ) {
-    for (mut hook, colliding_entities, _hook_tf) in hook_qy.iter_mut() {
+    for (hook_entity, mut hook, mut hook_transform, mut velocity) in hook_qy.iter_mut() {
         if hook.attached_to.is_some() {
             continue;
         }
+        // Nothing to fly back to
+        let Ok(shooter_transform) = shooter_transform_qy.get(hook.shooter_entity) else {
+            commands.entity(hook_entity).despawn();
+            continue;
+        };
+        let hook_pos = hook_transform.translation.truncate();
+        let shooter_pos = shooter_transform.translation.truncate();
 
-        let mut hit_enemy: Option<Entity> = None;
-
-        for collided_with in colliding_entities
-            .iter()
-            .copied()
-            .filter(|e| *e != hook.shooter_entity)
-        {
-            if enemy_qy.contains(collided_with) {
-                hit_enemy = Some(collided_with);
-                break;
+        if hook.retracting {
+            let to_shooter = shooter_pos - hook_pos;
+            let step_distance = grappling_hook_config.hook_speed * time.delta_secs();
+            if to_shooter.length() <= step_distance {
+                commands.entity(hook_entity).despawn();
+                commands.entity(hook.shooter_entity).remove::<Grappling>();
+            } else {
+                velocity.0 = to_shooter.normalize() * grappling_hook_config.hook_speed;
             }
+            continue;
         }
 
-        if let Some(enemy_entity) = hit_enemy {
-            hook.attached_to = Some(GrapplingHookAttachmentType::Enemy(enemy_entity));
-            commands.trigger(GrappleAttachedEvent {
-                entity: hook.shooter_entity,
-                attachment_type: GrapplingHookAttachmentType::Enemy(enemy_entity),
-            });
-        } else {
-            hook.attached_to = Some(GrapplingHookAttachmentType::World);
+        if hook_pos.distance(shooter_pos) > grappling_hook_config.max_range {
+            hook.retracting = true;
+            continue;
+        }
+
+        let Ok(direction) = Dir2::new(velocity.0) else {
+            continue;
+        };
+        let filter = SpatialQueryFilter::from_excluded_entities([hook_entity, hook.shooter_entity])
+            .with_mask(GameLayer::Default);
+        let hit = spatial_query.cast_ray_predicate(
+            hook_pos,
+            direction,
+            velocity.length() * time.delta_secs(),
+            true,
+            &filter,
+            &|entity| {
+                ground_qy.contains(entity) || enemy_qy.contains(entity) || prop_qy.contains(entity)
+            },
+        );
+        if let Some(hit_data) = hit {
+            let hit_point = hook_pos + direction.as_vec2() * hit_data.distance;
+            let attachment_type = if let Ok(prop_transform) = prop_qy.get(hit_data.entity) {
+                hook.prop_local_offset = prop_transform
+                    .affine()
+                    .inverse()
+                    .transform_point3(hit_point.extend(0.))
+                    .truncate();
+                GrapplingHookAttachmentType::Prop(hit_data.entity)
+            } else if enemy_qy.contains(hit_data.entity) {
+                GrapplingHookAttachmentType::Enemy(hit_data.entity)
+            } else {
+                GrapplingHookAttachmentType::World
+            };
+            hook_transform.translation = hit_point.extend(hook_transform.translation.z);
+            velocity.0 = Vec2::ZERO;
+            hook.attached_to = Some(attachment_type);
             commands.trigger(GrappleAttachedEvent {
                 entity: hook.shooter_entity,
-                attachment_type: GrapplingHookAttachmentType::World,
+                attachment_type,
             });
         }
     }
@@ -165,11 +387,21 @@ fn hook_attachment_observer(
     transforms: Query<&Transform, With<RigidBody>>,
     // to find the hook entity for this shooter and get its world pos if needed
     hook_q: Query<(Entity, &GrapplingHook, &Transform)>,
+    prop_kind_qy: Query<&Prop>,
+    grappling_hook_config: Res<GrapplingHookConfig>,
 ) {
     let shooter = grapple_attached_event.entity;
 
     match grapple_attached_event.attachment_type {
-        GrapplingHookAttachmentType::World => {
+        GrapplingHookAttachmentType::Prop(prop) if !is_swung_from(&prop_kind_qy, prop) => {
+            if let Some((hook_entity, _hook, _hook_tf)) =
+                hook_q.iter().find(|(_, h, _)| h.shooter_entity == shooter)
+            {
+                commands.entity(shooter).insert(PullingProp { hook_entity, prop });
+            }
+        }
+        // Moving platforms are swung from like the world, with the anchor following the platform
+        GrapplingHookAttachmentType::World | GrapplingHookAttachmentType::Prop(_) => {
             // Find the hook belonging to this shooter to get the anchor position
             if let (Ok(shooter_tf), Some((hook_entity, _hook, hook_tf))) = (
                 transforms.get(shooter),
@@ -178,11 +410,23 @@ fn hook_attachment_observer(
                 let anchor = hook_tf.translation.truncate();
                 let rope_length = shooter_tf.translation.truncate().distance(anchor);
 
+                let rope_joint = match grappling_hook_config.rope_mode {
+                    RopeMode::Spring => None,
+                    RopeMode::Joint => Some(spawn_rope_joint(
+                        &mut commands,
+                        shooter,
+                        anchor,
+                        rope_length,
+                    )),
+                };
+
                 commands.entity(shooter).insert(Swinging {
                     hook_entity,
                     anchor,
                     rope_rest_length: rope_length,
                     previous_distance_from_hook: None,
+                    pivots: Vec::new(),
+                    rope_joint,
                 });
             }
         }
@@ -194,7 +438,8 @@ fn hook_attachment_observer(
                 let rope_length = shooter_tf
                     .translation
                     .truncate()
-                    .distance(hook_tf.translation.truncate());
+                    .distance(hook_tf.translation.truncate())
+                    .min(grappling_hook_config.enemy_leash_length);
 
                 commands.entity(shooter).insert(PullingEnemy {
                     hook_entity: entity,
@@ -211,6 +456,25 @@ fn hook_attachment_observer(
 pub struct GrapplingHookConfig {
     pub spring_force: f32,
     pub spring_dampening: f32,
+    pub hook_speed: f32,
+    // Seconds between firing the hook and being able to fire it again
+    pub cooldown: f32,
+    // Max distance from the shooter the hook can fly before retracting
+    pub max_range: f32,
+    // Rope length change per second while holding the reel keys
+    pub reel_speed: f32,
+    // Rope length change per scroll wheel notch
+    pub scroll_reel_step: f32,
+    pub min_rope_length: f32,
+    pub max_rope_length: f32,
+    // Rope implementation used for swings started after changing it
+    pub rope_mode: RopeMode,
+    // Speed hooked enemies are reeled in at
+    pub enemy_reel_speed: f32,
+    // Longest the rope to a hooked enemy can be, enemies further away get dragged along
+    pub enemy_leash_length: f32,
+    // Slam reeled in enemies on arrival instead of just holding them
+    pub slam_on_arrival: bool,
 }
 
 impl Default for GrapplingHookConfig {
@@ -218,6 +482,17 @@ impl Default for GrapplingHookConfig {
         GrapplingHookConfig {
             spring_force: DEFAULT_GRAPPLING_HOOK_SPRING_FORCE,
             spring_dampening: DEFAULT_GRAPPLING_HOOK_DAMPENING,
+            hook_speed: DEFAULT_GRAPPLING_HOOK_SPEED,
+            cooldown: DEFAULT_GRAPPLE_COOLDOWN,
+            max_range: DEFAULT_GRAPPLING_HOOK_MAX_RANGE,
+            reel_speed: DEFAULT_ROPE_REEL_SPEED,
+            scroll_reel_step: DEFAULT_ROPE_SCROLL_REEL_STEP,
+            min_rope_length: DEFAULT_MIN_ROPE_LENGTH,
+            max_rope_length: DEFAULT_GRAPPLING_HOOK_MAX_RANGE,
+            rope_mode: RopeMode::default(),
+            enemy_reel_speed: DEFAULT_ENEMY_REEL_SPEED,
+            enemy_leash_length: DEFAULT_ENEMY_LEASH_LENGTH,
+            slam_on_arrival: true,
         }
     }
 }
@@ -228,10 +503,15 @@ fn grappling_hook_swinging_spring_force_system_fixed(
     grappling_hook_spring_force: Res<GrapplingHookConfig>,
 ) {
     for (mut force, transform, swinging) in qy {
-        let distance_to_hook = transform.translation.truncate().distance(swinging.anchor);
-        let spring_discomfort = distance_to_hook - swinging.rope_rest_length;
+        // The joint keeps the rope length itself
+        if swinging.rope_joint.is_some() {
+            continue;
+        }
+        let anchor = swinging.current_anchor();
+        let distance_to_hook = transform.translation.truncate().distance(anchor);
+        let spring_discomfort = distance_to_hook - swinging.free_rope_rest_length();
         let spring_force_1d = spring_discomfort * grappling_hook_spring_force.spring_force;
-        let direction_to_hook = (swinging.anchor - transform.translation.truncate()).normalize();
+        let direction_to_hook = (anchor - transform.translation.truncate()).normalize();
         let spring_force_on_entity = direction_to_hook * spring_force_1d;
         force.apply_linear_impulse(spring_force_on_entity * time.delta_secs());
     }
@@ -243,10 +523,14 @@ fn damp_hook_spring_oscillation(
     grappling_hook_config: Res<GrapplingHookConfig>,
 ) {
     for (mut force, transform, mut swinging) in qy {
-        let distance_to_hook = transform.translation.truncate().distance(swinging.anchor);
+        if swinging.rope_joint.is_some() {
+            continue;
+        }
+        let anchor = swinging.current_anchor();
+        let distance_to_hook = transform.translation.truncate().distance(anchor);
         if let Some(previous_distance_from_hook) = swinging.previous_distance_from_hook {
             let delta_distance_to_hook = distance_to_hook - previous_distance_from_hook;
-            let direction_to_hook = (swinging.anchor - transform.translation.truncate()).normalize();
+            let direction_to_hook = (anchor - transform.translation.truncate()).normalize();
             let spring_dampening_force_1d = delta_distance_to_hook * grappling_hook_config.spring_dampening;
             let spring_dampening_force_on_entity = spring_dampening_force_1d * direction_to_hook;
             force.apply_linear_impulse(spring_dampening_force_on_entity * time.delta_secs());
@@ -255,6 +539,161 @@ fn damp_hook_spring_oscillation(
     }
 }
 
+fn spawn_rope_joint(
+    commands: &mut Commands,
+    swinger: Entity,
+    anchor: Vec2,
+    length: f32,
+) -> RopeJoint {
+    let anchor_body = commands
+        .spawn((
+            GameEntity::LevelEntity,
+            RigidBody::Kinematic,
+            Transform::from_translation(anchor.extend(0.)),
+        ))
+        .id();
+    let joint = commands
+        .spawn((
+            GameEntity::LevelEntity,
+            // Rope style, only the max length is limited so the rope can go slack
+            DistanceJoint::new(anchor_body, swinger).with_limits(0., length),
+        ))
+        .id();
+    RopeJoint {
+        anchor_body,
+        joint,
+        length,
+    }
+}
+
+/// Keeps the joint anchor body on the current pivot and the joint length in sync with reeling
+/// and rope wrapping
+fn fixed_sync_rope_joint(
+    mut swinging_qy: Query<(Entity, &mut Swinging)>,
+    mut anchor_transform_qy: Query<&mut Transform, Without<Swinging>>,
+    mut commands: Commands,
+) {
+    for (entity, mut swinging) in swinging_qy.iter_mut() {
+        let current_anchor = swinging.current_anchor();
+        let free_rope_length = swinging.free_rope_rest_length();
+        let Some(rope_joint) = swinging.rope_joint.as_mut() else {
+            continue;
+        };
+        if let Ok(mut anchor_transform) = anchor_transform_qy.get_mut(rope_joint.anchor_body) {
+            anchor_transform.translation = current_anchor.extend(0.);
+        }
+        if rope_joint.length != free_rope_length {
+            rope_joint.length = free_rope_length;
+            commands.entity(rope_joint.joint).insert(
+                DistanceJoint::new(rope_joint.anchor_body, entity).with_limits(0., free_rope_length),
+            );
+        }
+    }
+}
+
+/// Wraps the rope around `Ground` corners that get between the swinger and the current anchor,
+/// and unwraps the last pivot once the swinger swings back past it
+fn fixed_wrap_rope(
+    spatial_query: SpatialQuery,
+    mut swinging_qy: Query<(Entity, &Transform, &mut Swinging)>,
+    ground_qy: Query<&ColliderAabb, With<Ground>>,
+) {
+    for (entity, transform, mut swinging) in swinging_qy.iter_mut() {
+        let swinger_pos = transform.translation.truncate();
+
+        // Unwrap when the rope on both sides of the last pivot lines up again
+        if let Some(last_pivot) = swinging.pivots.last().copied() {
+            let previous = swinging.previous_anchor();
+            let side = (last_pivot.position - previous).perp_dot(swinger_pos - last_pivot.position);
+            if side * last_pivot.winding < 0. {
+                swinging.pivots.pop();
+                // The distance to the anchor jumps, so don't damp against the old one
+                swinging.previous_distance_from_hook = None;
+                continue;
+            }
+        }
+
+        let anchor = swinging.current_anchor();
+        let to_anchor = anchor - swinger_pos;
+        let Ok(direction) = Dir2::new(to_anchor) else {
+            continue;
+        };
+        let filter =
+            SpatialQueryFilter::from_excluded_entities([entity]).with_mask(GameLayer::Default);
+        let hit = spatial_query.cast_ray_predicate(
+            swinger_pos,
+            direction,
+            // Stop short of the anchor so the surface the anchor sits on doesn't count
+            to_anchor.length() - ROPE_WRAP_MARGIN,
+            true,
+            &filter,
+            &|entity| ground_qy.contains(entity),
+        );
+        let Some(hit_data) = hit else {
+            continue;
+        };
+        let Ok(ground_aabb) = ground_qy.get(hit_data.entity) else {
+            continue;
+        };
+        let hit_point = swinger_pos + direction.as_vec2() * hit_data.distance;
+        let corner = rope_wrap_corner(ground_aabb, hit_point);
+        let winding = (corner - anchor).perp_dot(swinger_pos - corner);
+        if winding != 0. {
+            swinging.pivots.push(RopePivot {
+                position: corner,
+                winding,
+            });
+            swinging.previous_distance_from_hook = None;
+        }
+    }
+}
+
+/// Corner of `aabb` closest to `point`, pushed slightly outwards from the box
+fn rope_wrap_corner(aabb: &ColliderAabb, point: Vec2) -> Vec2 {
+    let corners = [
+        aabb.min,
+        vec2(aabb.max.x, aabb.min.y),
+        aabb.max,
+        vec2(aabb.min.x, aabb.max.y),
+    ];
+    let closest_corner = corners
+        .into_iter()
+        .min_by(|a, b| a.distance_squared(point).total_cmp(&b.distance_squared(point)))
+        .unwrap_or(point);
+    let aabb_center = (aabb.min + aabb.max) / 2.;
+    let outwards = (closest_corner - aabb_center).normalize_or_zero();
+    closest_corner + outwards * ROPE_WRAP_MARGIN
+}
+
+fn is_swung_from(prop_kind_qy: &Query<&Prop>, prop: Entity) -> bool {
+    prop_kind_qy
+        .get(prop)
+        .is_ok_and(|prop| prop.0 == PropKind::MovingPlatform)
+}
+
+/// Keeps hooks attached to props on the spot they hit, and the swinging anchor on the hook
+fn hook_follow_prop(
+    mut hook_qy: Query<(&mut Transform, &GrapplingHook)>,
+    prop_transform_qy: Query<&GlobalTransform, With<Prop>>,
+    mut swinging_qy: Query<&mut Swinging>,
+) {
+    for (mut transform, hook) in hook_qy.iter_mut() {
+        let Some(GrapplingHookAttachmentType::Prop(prop_entity)) = hook.attached_to else {
+            continue;
+        };
+        let Ok(prop_transform) = prop_transform_qy.get(prop_entity) else {
+            continue;
+        };
+        let hook_pos = prop_transform
+            .transform_point(hook.prop_local_offset.extend(0.))
+            .truncate();
+        transform.translation = hook_pos.extend(transform.translation.z);
+        if let Ok(mut swinging) = swinging_qy.get_mut(hook.shooter_entity) {
+            swinging.anchor = hook_pos;
+        }
+    }
+}
+
 fn hook_follow_enemy(
     hook_qy: Query<(&mut Transform, &GrapplingHook), Without<Enemy>>,
     enemy_transform_qy: Query<&Transform, With<Enemy>>,
@@ -270,15 +709,124 @@ fn hook_follow_enemy(
     }
 }
 
+/// Reels hooked enemies in towards the shooter, never letting them get further away than the rope
+fn fixed_reel_in_enemy(
+    mut pulling_qy: Query<(Entity, &Transform, &mut PullingEnemy)>,
+    mut enemy_qy: Query<(&Transform, &mut LinearVelocity), (With<Enemy>, Without<PullingEnemy>)>,
+    grappling_hook_config: Res<GrapplingHookConfig>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (shooter, shooter_transform, mut pulling_enemy) in pulling_qy.iter_mut() {
+        let Ok((enemy_transform, mut enemy_velocity)) = enemy_qy.get_mut(pulling_enemy.enemy)
+        else {
+            continue;
+        };
+        pulling_enemy.rope_length = (pulling_enemy.rope_length
+            - grappling_hook_config.enemy_reel_speed * time.delta_secs())
+        .max(grappling_hook_config.min_rope_length);
+
+        let to_shooter =
+            shooter_transform.translation.truncate() - enemy_transform.translation.truncate();
+        let distance = to_shooter.length();
+        if distance <= GRAPPLE_SLAM_DISTANCE && grappling_hook_config.slam_on_arrival {
+            commands.trigger(GrappleSlam {
+                entity: shooter,
+                enemy: pulling_enemy.enemy,
+            });
+            continue;
+        }
+
+        let direction = to_shooter.normalize_or_zero();
+        // Pull harder the further past the rope length the enemy is
+        let leash_correction = ((distance - pulling_enemy.rope_length).max(0.)
+            * LEASH_CORRECTION_RATE)
+            .min(MAX_LEASH_CORRECTION_SPEED);
+        let pull_speed = grappling_hook_config.enemy_reel_speed + leash_correction;
+        // Only speed up along the rope, falling and sliding sideways still happen
+        let speed_towards_shooter = enemy_velocity.0.dot(direction);
+        if speed_towards_shooter < pull_speed {
+            enemy_velocity.0 += direction * (pull_speed - speed_towards_shooter);
+        }
+    }
+}
+
+/// The shooter slams a reeled in enemy, damaging, stunning and knocking it away
+#[derive(EntityEvent)]
+pub struct GrappleSlam {
+    pub entity: Entity,
+    pub enemy: Entity,
+}
+
+fn grapple_slam_observer(
+    grapple_slam_event: On<GrappleSlam>,
+    shooter_qy: Query<&Transform>,
+    mut enemy_qy: Query<(&Transform, &mut Health, &mut LinearVelocity), With<Enemy>>,
+    mut commands: Commands,
+) {
+    let Ok(shooter_transform) = shooter_qy.get(grapple_slam_event.entity) else {
+        return;
+    };
+    if let Ok((enemy_transform, mut health, mut velocity)) =
+        enemy_qy.get_mut(grapple_slam_event.enemy)
+    {
+        health.take_damage(GRAPPLE_SLAM_DAMAGE);
+        let away_from_shooter = (enemy_transform.translation - shooter_transform.translation)
+            .truncate()
+            .normalize_or_zero();
+        velocity.0 = (away_from_shooter + Vec2::Y).normalize_or_zero() * GRAPPLE_SLAM_KNOCKBACK;
+        commands.trigger(ApplyStatusEffect {
+            entity: grapple_slam_event.enemy,
+            effect: GRAPPLE_SLAM_STUN,
+        });
+    }
+    commands.trigger(EndGrapple {
+        entity: grapple_slam_event.entity,
+    });
+}
+
+/// Lets go of the rope when whatever it's attached to gets despawned
+fn release_grapple_on_lost_target(
+    pulling_enemy_qy: Query<(Entity, &PullingEnemy)>,
+    pulling_prop_qy: Query<(Entity, &PullingProp)>,
+    enemy_qy: Query<(), With<Enemy>>,
+    prop_qy: Query<(), With<Prop>>,
+    mut commands: Commands,
+) {
+    for (entity, pulling_enemy) in pulling_enemy_qy.iter() {
+        if !enemy_qy.contains(pulling_enemy.enemy) {
+            commands.trigger(EndGrapple { entity });
+        }
+    }
+    for (entity, pulling_prop) in pulling_prop_qy.iter() {
+        if !prop_qy.contains(pulling_prop.prop) {
+            commands.trigger(EndGrapple { entity });
+        }
+    }
+}
+
 fn draw_grapple_line(
     mut gizmos: Gizmos,
     swinging_query: Query<(&Transform, &Swinging)>,
     pulling_query: Query<(&Transform, &PullingEnemy)>,
+    pulling_prop_query: Query<(&Transform, &PullingProp)>,
     hook_transform_query: Query<&Transform, With<GrapplingHook>>,
+    flying_hook_query: Query<(&Transform, &GrapplingHook)>,
+    shooter_transform_query: Query<&Transform, With<Grappling>>,
 ) {
-    // Draw line from swinging entities to hook
+    // Draw rope from swinging entities to hook, going around the pivots it is wrapped around
     for (entity_transform, swinging) in swinging_query.iter() {
         if let Ok(hook_transform) = hook_transform_query.get(swinging.hook_entity) {
+            let rope_points = std::iter::once(hook_transform.translation.truncate())
+                .chain(swinging.pivots.iter().map(|pivot| pivot.position))
+                .chain(std::iter::once(entity_transform.translation.truncate()));
+            gizmos.linestrip_2d(rope_points, Color::WHITE);
+        }
+    }
+    
+    // Draw line from entities pulling enemies to hook
+    for (entity_transform, pulling_enemy) in pulling_query.iter() {
+        if let Ok(hook_transform) = hook_transform_query.get(pulling_enemy.hook_entity) {
             gizmos.line_2d(
                 entity_transform.translation.truncate(),
                 hook_transform.translation.truncate(),
@@ -286,10 +834,10 @@ fn draw_grapple_line(
             );
         }
     }
-    
-    // Draw line from entities pulling enemies to hook
-    for (entity_transform, pulling_enemy) in pulling_query.iter() {
-        if let Ok(hook_transform) = hook_transform_query.get(pulling_enemy.hook_entity) {
+
+    // Draw line from entities pulling props to hook
+    for (entity_transform, pulling_prop) in pulling_prop_query.iter() {
+        if let Ok(hook_transform) = hook_transform_query.get(pulling_prop.hook_entity) {
             gizmos.line_2d(
                 entity_transform.translation.truncate(),
                 hook_transform.translation.truncate(),
@@ -297,6 +845,42 @@ fn draw_grapple_line(
             );
         }
     }
+
+    // Draw line from shooters to hooks that are still flying
+    for (hook_transform, hook) in flying_hook_query.iter() {
+        if hook.attached_to.is_some() {
+            continue;
+        }
+        if let Ok(shooter_transform) = shooter_transform_query.get(hook.shooter_entity) {
+            gizmos.line_2d(
+                shooter_transform.translation.truncate(),
+                hook_transform.translation.truncate(),
+                Color::WHITE,
+            );
+        }
+    }
+}
+
+/// Shortens (negative) or lengthens (positive) the rope of a swinging entity
+#[derive(EntityEvent)]
+pub struct ReelRope {
+    pub entity: Entity,
+    pub length_delta: f32,
+}
+
+fn reel_rope_event_observer(
+    reel_rope_event: On<ReelRope>,
+    mut swinging_qy: Query<&mut Swinging>,
+    grappling_hook_config: Res<GrapplingHookConfig>,
+) {
+    if let Ok(mut swinging) = swinging_qy.get_mut(reel_rope_event.entity) {
+        // Rope wrapped around pivots can't be reeled in, only the free segment can
+        let wrapped_length = swinging.rope_rest_length - swinging.free_rope_rest_length();
+        let min_length = wrapped_length + grappling_hook_config.min_rope_length;
+        let max_length = grappling_hook_config.max_rope_length.max(min_length);
+        swinging.rope_rest_length = (swinging.rope_rest_length + reel_rope_event.length_delta)
+            .clamp(min_length, max_length);
+    }
 }
 
 #[derive(EntityEvent)]
@@ -306,35 +890,55 @@ pub struct EndGrapple {
 
 fn end_grapple_event_observer(
     end_grapple_event: On<EndGrapple>,
-    entity_pulling_enemy: Query<(Entity, &PullingEnemy, &Transform)>,
+    entity_pulling_enemy: Query<(Entity, &PullingEnemy)>,
     entities_swinging: Query<(Entity, &Swinging, &Transform)>,
-    mut enemy_qy: Query<(Forces, &Transform), With<Enemy>>,
+    entities_pulling_prop: Query<(Entity, &PullingProp, &Transform)>,
+    mut hook_qy: Query<&mut GrapplingHook>,
     mut commands: Commands,
 ) {
-    if let Ok((entity, pulling_enemy_component, transform)) =
+    if let Ok((entity, pulling_enemy_component)) =
         entity_pulling_enemy.get(end_grapple_event.entity)
     {
-        if let Ok((mut enemy_avian_forces, enemy_transform)) =
-            enemy_qy.get_mut(pulling_enemy_component.enemy)
-        {
-            let translation_delta = transform.translation - enemy_transform.translation;
-            let normalized_delta = translation_delta.normalize_or_zero().truncate();
-            let force = normalized_delta * GRAPPLE_ENEMY_PULL_FORCE;
-            enemy_avian_forces.apply_linear_impulse(force);
-            commands.entity(entity).remove::<Grappling>();
-            commands.entity(entity).remove::<PullingEnemy>();
-            commands
-                .entity(pulling_enemy_component.hook_entity)
-                .despawn();
-        } else {
-            warn!(
-                "Enemy {:?} being pulled no longer has a RigidBody!",
-                pulling_enemy_component.enemy
-            );
-        }
+        // The enemy was reeled in while holding, letting go just drops it
+        commands.entity(entity).remove::<Grappling>();
+        commands.entity(entity).remove::<PullingEnemy>();
+        commands
+            .entity(pulling_enemy_component.hook_entity)
+            .despawn();
     } else if let Ok((entity, swinging, _)) = entities_swinging.get(end_grapple_event.entity) {
         commands.entity(entity).remove::<Grappling>();
         commands.entity(entity).remove::<Swinging>();
         commands.entity(swinging.hook_entity).despawn();
+        if let Some(rope_joint) = swinging.rope_joint {
+            commands.entity(rope_joint.joint).despawn();
+            commands.entity(rope_joint.anchor_body).despawn();
+        }
+    } else if let Ok((entity, pulling_prop, transform)) =
+        entities_pulling_prop.get(end_grapple_event.entity)
+    {
+        commands.trigger(YankProp {
+            entity: pulling_prop.prop,
+            towards: transform.translation.truncate(),
+        });
+        commands.entity(entity).remove::<Grappling>();
+        commands.entity(entity).remove::<PullingProp>();
+        commands.entity(pulling_prop.hook_entity).despawn();
+    } else {
+        // Released while the hook is still flying, bring it back
+        for mut hook in hook_qy.iter_mut() {
+            if hook.shooter_entity == end_grapple_event.entity && hook.attached_to.is_none() {
+                hook.retracting = true;
+            }
+        }
+    }
+}
+
+/// The grapple's recharge time in the ability registry comes from the config
+fn sync_grapple_cooldown(
+    grappling_hook_config: Res<GrapplingHookConfig>,
+    mut ability_registry: ResMut<AbilityRegistry>,
+) {
+    if let Some(definition) = ability_registry.get_mut(GRAPPLE_ABILITY) {
+        definition.recharge_time = grappling_hook_config.cooldown;
     }
 }
@@ -0,0 +1,245 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::abilities::ability::*;
+use crate::abilities::bullet_time::BulletTime;
+use crate::abilities::dash::Dashing;
+use crate::enemy::Enemy;
+use crate::game_data::*;
+use crate::grounded::{GroundDetector, Grounded};
+use crate::status_effects::*;
+
+const DEFAULT_GROUND_POUND_SPEED: f32 = 2500.;
+const DEFAULT_GROUND_POUND_RADIUS: f32 = 250.;
+const DEFAULT_GROUND_POUND_DAMAGE: f32 = 15.;
+const DEFAULT_GROUND_POUND_KNOCKBACK: f32 = 900.;
+const GROUND_POUND_COOLDOWN: f32 = 1.;
+// Below this fraction of the launch speed the pound counts as stopped by whatever is below
+const GROUND_POUND_STOPPED_FRACTION: f32 = 0.5;
+const GROUND_POUND_FLASH_DURATION: f32 = 0.2;
+const GROUND_POUND_STUN: StatusEffect = StatusEffect {
+    kind: StatusEffectKind::Stun,
+    duration: 0.75,
+    magnitude: 0.,
+};
+
+pub const GROUND_POUND_ABILITY: AbilityId = AbilityId("ground_pound");
+
+pub struct GroundPoundPlugin;
+impl Plugin for GroundPoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GroundPoundConfig>()
+            .register_type::<GroundPoundConfig>()
+            .register_ability(
+                GROUND_POUND_ABILITY,
+                AbilityDefinition {
+                    name: "Ground pound",
+                    icon: None,
+                    default_input: AbilityInput::Key(KeyCode::KeyF),
+                    activate_on: ActivateOn::Press,
+                    max_charges: 1,
+                    recharge_time: GROUND_POUND_COOLDOWN,
+                },
+            )
+            .add_observer(activate_ground_pound)
+            .add_observer(ground_pound_landed)
+            .add_systems(
+                FixedUpdate,
+                ground_pound_landing_system.run_if(in_state(GameState::PlayingLevel)),
+            )
+            .add_systems(
+                Update,
+                draw_ground_pound_flashes.run_if(in_state(GameState::PlayingLevel)),
+            );
+    }
+}
+
+#[derive(Reflect, Resource)]
+#[reflect(Resource)]
+pub struct GroundPoundConfig {
+    // Speed the entity is launched straight down at
+    pub speed: f32,
+    pub radius: f32,
+    // Dealt to enemies at the center of the impact, falling off towards the edge
+    pub damage: f32,
+    pub knockback: f32,
+}
+
+impl Default for GroundPoundConfig {
+    fn default() -> Self {
+        GroundPoundConfig {
+            speed: DEFAULT_GROUND_POUND_SPEED,
+            radius: DEFAULT_GROUND_POUND_RADIUS,
+            damage: DEFAULT_GROUND_POUND_DAMAGE,
+            knockback: DEFAULT_GROUND_POUND_KNOCKBACK,
+        }
+    }
+}
+
+/// Can slam down from mid-air
+#[derive(Component)]
+#[require(AbilityCharges, GroundDetector)]
+pub struct CanGroundPound;
+
+#[derive(Component)]
+pub struct GroundPounding {
+    speed: f32,
+    // Gravity scale to restore on landing, None if there was no GravityScale
+    previous_gravity_scale: Option<f32>,
+}
+
+fn activate_ground_pound(
+    activate_ability_event: On<ActivateAbility>,
+    mut ground_pounder_qy: Query<
+        (
+            &mut LinearVelocity,
+            Option<&mut GravityScale>,
+            &mut AbilityCharges,
+            Has<Grounded>,
+        ),
+        (With<CanGroundPound>, Without<GroundPounding>, Without<Dashing>),
+    >,
+    ground_pound_config: Res<GroundPoundConfig>,
+    ability_registry: Res<AbilityRegistry>,
+    bullet_time: Res<BulletTime>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    if activate_ability_event.ability != GROUND_POUND_ABILITY {
+        return;
+    }
+    let entity = activate_ability_event.entity;
+    let Ok((mut velocity, gravity_opt, mut ability_charges, is_grounded)) =
+        ground_pounder_qy.get_mut(entity)
+    else {
+        return;
+    };
+    // Only from mid-air
+    if is_grounded {
+        return;
+    }
+    if !ability_charges.try_use(GROUND_POUND_ABILITY, &ability_registry, time.elapsed_secs()) {
+        return;
+    }
+    let speed = ground_pound_config.speed * bullet_time.player_time_factor();
+    velocity.0 = Vec2::NEG_Y * speed;
+    let previous_gravity_scale = gravity_opt.as_ref().map(|gravity_scale| gravity_scale.0);
+    if let Some(mut gravity_scale) = gravity_opt {
+        *gravity_scale = GravityScale(0.0);
+    } else {
+        commands.entity(entity).insert(GravityScale(0.0));
+    }
+    commands.entity(entity).insert(GroundPounding {
+        speed,
+        previous_gravity_scale,
+    });
+}
+
+fn ground_pound_landing_system(
+    ground_pounding_qy: Query<(Entity, &GroundPounding, &LinearVelocity, Has<Grounded>)>,
+    mut commands: Commands,
+) {
+    for (entity, ground_pounding, velocity, is_grounded) in ground_pounding_qy.iter() {
+        // Landing on an enemy or a prop stops the fall too
+        let stopped = -velocity.y < ground_pounding.speed * GROUND_POUND_STOPPED_FRACTION;
+        if stopped || is_grounded {
+            commands.trigger(GroundPoundLanded { entity });
+        }
+    }
+}
+
+#[derive(EntityEvent)]
+pub struct GroundPoundLanded {
+    pub entity: Entity,
+}
+
+#[derive(Component)]
+struct GroundPoundFlash {
+    position: Vec2,
+    radius: f32,
+    start_time: f32,
+}
+
+/// Damages, stuns and knocks away enemies around where the ground pound landed
+fn ground_pound_landed(
+    ground_pound_landed_event: On<GroundPoundLanded>,
+    mut ground_pounding_qy: Query<(&GroundPounding, &Transform, &mut LinearVelocity)>,
+    mut enemy_qy: Query<
+        (&Transform, &mut Health, &mut LinearVelocity),
+        (With<Enemy>, Without<Invulnerable>, Without<GroundPounding>),
+    >,
+    spatial_query: SpatialQuery,
+    ground_pound_config: Res<GroundPoundConfig>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    let entity = ground_pound_landed_event.entity;
+    let Ok((ground_pounding, transform, mut velocity)) = ground_pounding_qy.get_mut(entity) else {
+        return;
+    };
+    velocity.0 = Vec2::ZERO;
+    match ground_pounding.previous_gravity_scale {
+        Some(previous_gravity_scale) => {
+            commands
+                .entity(entity)
+                .insert(GravityScale(previous_gravity_scale));
+        }
+        None => {
+            commands.entity(entity).remove::<GravityScale>();
+        }
+    }
+    commands.entity(entity).remove::<GroundPounding>();
+
+    let impact_pos = transform.translation.truncate();
+    let radius = ground_pound_config.radius;
+    let filter = SpatialQueryFilter::from_excluded_entities([entity]);
+    for hit_entity in
+        spatial_query.shape_intersections(&Collider::circle(radius), impact_pos, 0., &filter)
+    {
+        let Ok((enemy_transform, mut health, mut enemy_velocity)) = enemy_qy.get_mut(hit_entity)
+        else {
+            continue;
+        };
+        let delta = enemy_transform.translation.truncate() - impact_pos;
+        // 1 at the center of the impact, 0 at the edge
+        let falloff = (1. - delta.length() / radius).clamp(0., 1.);
+        health.take_damage(ground_pound_config.damage * falloff);
+        // Knocked away sideways and up off the ground
+        let away = Vec2::new(delta.x.signum(), 1.).normalize();
+        enemy_velocity.0 = away * ground_pound_config.knockback * falloff;
+        commands.entity(hit_entity).remove::<Sleeping>();
+        commands.trigger(ApplyStatusEffect {
+            entity: hit_entity,
+            effect: GROUND_POUND_STUN,
+        });
+    }
+
+    commands.spawn((
+        GameEntity::LevelEntity,
+        GroundPoundFlash {
+            position: impact_pos,
+            radius,
+            start_time: time.elapsed_secs(),
+        },
+    ));
+}
+
+fn draw_ground_pound_flashes(
+    mut gizmos: Gizmos,
+    flash_qy: Query<(Entity, &GroundPoundFlash)>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, flash) in flash_qy.iter() {
+        let progress = (time.elapsed_secs() - flash.start_time) / GROUND_POUND_FLASH_DURATION;
+        if progress >= 1. {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        gizmos.circle_2d(
+            flash.position,
+            flash.radius * progress,
+            Color::srgba(0.9, 0.8, 0.6, 1. - progress),
+        );
+    }
+}
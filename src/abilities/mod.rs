@@ -1,16 +1,35 @@
 use bevy::prelude::*;
 
+pub mod ability;
+pub mod blink;
+pub mod bullet_time;
 pub mod dash;
 pub mod grapple;
+pub mod ground_pound;
+pub mod parry;
 
 pub struct AbilitiesPlugin;
 
 impl Plugin for AbilitiesPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins((dash::DashPlugin, grapple::GrapplePlugin));
+        // The first abilities registered make up the default loadout
+        app.add_plugins((
+            ability::AbilityFrameworkPlugin,
+            bullet_time::BulletTimePlugin,
+            dash::DashPlugin,
+            grapple::GrapplePlugin,
+            ground_pound::GroundPoundPlugin,
+            parry::ParryPlugin,
+            blink::BlinkPlugin,
+        ));
     }
 }
 
 // Re-exports for convenience
+pub use ability::*;
+pub use blink::*;
+pub use bullet_time::*;
 pub use dash::*;
-pub use grapple::*;
\ No newline at end of file
+pub use grapple::*;
+pub use ground_pound::*;
+pub use parry::*;
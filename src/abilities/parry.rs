@@ -0,0 +1,120 @@
+use bevy::prelude::*;
+
+use crate::abilities::ability::*;
+use crate::abilities::dash::DASH_ABILITY;
+use crate::game_data::*;
+use crate::projectiles::{ProjectileReflected, ReflectsProjectiles};
+
+// Seconds projectiles are reflected for after pressing parry
+const PARRY_WINDOW: f32 = 0.25;
+// Reflecting within this many seconds of pressing parry counts as a perfect parry
+const PERFECT_PARRY_WINDOW: f32 = 0.08;
+const PARRY_COOLDOWN: f32 = 1.;
+const PARRY_RADIUS: f32 = 60.;
+const PARRY_COLOR: Color = Color::srgb(0.6, 0.9, 1.0);
+const PERFECT_PARRY_COLOR: Color = Color::srgb(1.0, 0.9, 0.3);
+
+pub const PARRY_ABILITY: AbilityId = AbilityId("parry");
+
+pub struct ParryPlugin;
+impl Plugin for ParryPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_ability(
+            PARRY_ABILITY,
+            AbilityDefinition {
+                name: "Parry",
+                icon: None,
+                default_input: AbilityInput::Key(KeyCode::KeyQ),
+                activate_on: ActivateOn::Press,
+                max_charges: 1,
+                recharge_time: PARRY_COOLDOWN,
+            },
+        )
+        .add_observer(activate_parry)
+        .add_observer(parried_projectile)
+        .add_systems(
+            Update,
+            (end_parry_window, draw_parry).run_if(in_state(GameState::PlayingLevel)),
+        );
+    }
+}
+
+/// Can open a parry window that reflects projectiles
+#[derive(Component)]
+#[require(AbilityCharges)]
+pub struct CanParry;
+
+#[derive(Component)]
+pub struct Parrying {
+    start_time: f32,
+    // Whether a projectile has been perfectly parried during this window
+    perfect: bool,
+}
+
+fn activate_parry(
+    activate_ability_event: On<ActivateAbility>,
+    mut parry_qy: Query<&mut AbilityCharges, (With<CanParry>, Without<Parrying>)>,
+    ability_registry: Res<AbilityRegistry>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    if activate_ability_event.ability != PARRY_ABILITY {
+        return;
+    }
+    let entity = activate_ability_event.entity;
+    let Ok(mut ability_charges) = parry_qy.get_mut(entity) else {
+        return;
+    };
+    if !ability_charges.try_use(PARRY_ABILITY, &ability_registry, time.elapsed_secs()) {
+        return;
+    }
+    commands.entity(entity).insert((
+        Parrying {
+            start_time: time.elapsed_secs(),
+            perfect: false,
+        },
+        ReflectsProjectiles,
+    ));
+}
+
+fn end_parry_window(
+    parrying_qy: Query<(Entity, &Parrying)>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, parrying) in parrying_qy.iter() {
+        if time.elapsed_secs() - parrying.start_time > PARRY_WINDOW {
+            commands
+                .entity(entity)
+                .remove::<(Parrying, ReflectsProjectiles)>();
+        }
+    }
+}
+
+/// Perfect parries give back all dash charges
+fn parried_projectile(
+    projectile_reflected_event: On<ProjectileReflected>,
+    mut parrying_qy: Query<(&mut Parrying, &mut AbilityCharges)>,
+    time: Res<Time>,
+) {
+    let Ok((mut parrying, mut ability_charges)) =
+        parrying_qy.get_mut(projectile_reflected_event.entity)
+    else {
+        return;
+    };
+    if time.elapsed_secs() - parrying.start_time <= PERFECT_PARRY_WINDOW {
+        parrying.perfect = true;
+        ability_charges.refill(DASH_ABILITY);
+    }
+}
+
+fn draw_parry(mut gizmos: Gizmos, parrying_qy: Query<(&Parrying, &Transform)>) {
+    for (parrying, transform) in parrying_qy.iter() {
+        let color = if parrying.perfect {
+            PERFECT_PARRY_COLOR
+        } else {
+            PARRY_COLOR
+        };
+        gizmos.circle_2d(transform.translation.truncate(), PARRY_RADIUS, color);
+    }
+}
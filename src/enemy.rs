@@ -5,6 +5,7 @@ use crate::game_data::*;
 use crate::level::FacingDirection;
 use crate::player::*;
 use crate::projectiles::*;
+use crate::status_effects::*;
 
 const FOLLOW_PLAYER_FORCE: f32 = 1_500_000.;
 const STOP_FOLLOWING_PLAYER_DISTANCE: f32 = 400.;
@@ -15,6 +16,14 @@ pub struct BountyTarget;
 #[derive(Component)]
 pub struct Enemy;
 
+/// Enemy that lobs grenades at the player instead of shooting
+#[derive(Component)]
+pub struct Grenadier;
+
+/// Enemy that shoots instant hitscan shots at the player
+#[derive(Component)]
+pub struct Sniper;
+
 #[derive(Component)]
 struct ReadyToShoot;
 
@@ -48,20 +57,26 @@ impl Plugin for EnemyPlugin {
 
 fn walk_towards_player(
     mut enemy_qy: Query<
-        (Forces, &Transform, &mut FacingDirection),
-        (With<Enemy>, With<EnemySeesPlayer>, Without<BountyTarget>),
+        (Forces, &Transform, &mut FacingDirection, Option<&StatusEffects>),
+        (
+            With<Enemy>,
+            With<EnemySeesPlayer>,
+            Without<BountyTarget>,
+            Without<Stunned>,
+        ),
     >,
     player_transform_qy: Query<&Transform, With<Player>>,
 ) {
     if let Ok(player_transform) = player_transform_qy.single() {
-        for (mut force, transform, mut facing_direction) in enemy_qy.iter_mut() {
+        for (mut force, transform, mut facing_direction, status_effects_opt) in enemy_qy.iter_mut() {
             let player_pos = player_transform.translation.truncate();
             let enemy_pos = transform.translation.truncate();
             let delta_pos = player_pos - enemy_pos;
             let distance = delta_pos.length();
             if distance > STOP_FOLLOWING_PLAYER_DISTANCE {
                 let direction = delta_pos / distance;
-                force.apply_force(direction * FOLLOW_PLAYER_FORCE);
+                let movement_scale = status_effects_opt.map_or(1., |effects| effects.movement_scale());
+                force.apply_force(direction * FOLLOW_PLAYER_FORCE * movement_scale);
                 if direction.x > 0. {
                     *facing_direction = FacingDirection::Right;
                 } else {
@@ -77,6 +92,7 @@ fn fixed_look_for_player(
     spatial_query: SpatialQuery,
     enemies: Query<(Entity, &Transform), With<Enemy>>,
     players: Query<(Entity, &Transform), With<Player>>,
+    child_of_qy: Query<&ChildOf>,
     mut commands: Commands,
 ) {
     // If there's not exactly one player, bail out early.
@@ -100,9 +116,23 @@ fn fixed_look_for_player(
         let dir = (player_pos - enemy_pos).normalize();
 
         let dir2 = Dir2::new(dir).expect("invalid direction for Dir2");
-        let filter = SpatialQueryFilter::from_excluded_entities(enemy_entity.iter());
+        // Hitboxes overlap their character's collider, so only look at the default layer
+        let filter = SpatialQueryFilter::from_excluded_entities(enemy_entity.iter())
+            .with_mask(GameLayer::Default);
 
-        let hit = spatial_query.cast_ray(enemy_pos, dir2, distance_to_player, true, &filter);
+        // Looking past what the enemy is holding, like its shield
+        let hit = spatial_query.cast_ray_predicate(
+            enemy_pos,
+            dir2,
+            distance_to_player,
+            true,
+            &filter,
+            &|entity| {
+                child_of_qy
+                    .get(entity)
+                    .map_or(true, |child_of| child_of.parent() != enemy_entity)
+            },
+        );
 
         if let Some(hit_data) = hit {
             if hit_data.entity == player_entity {
@@ -115,13 +145,33 @@ fn fixed_look_for_player(
 }
 
 const PROJECTILE_DAMAGE: f32 = 10.;
+const GRENADE_SLOW: StatusEffect = StatusEffect {
+    kind: StatusEffectKind::Slow,
+    duration: 2.,
+    magnitude: 0.5,
+};
+const GRENADE_EXPLOSIVE: Explosive = Explosive {
+    radius: 150.,
+    damage: 25.,
+    knockback: 300_000.,
+    explode_on_impact: true,
+};
+const SNIPER_DAMAGE: f32 = 25.;
+// How much the grenade throw is tilted upwards to make it arc towards the player
+const GRENADE_THROW_UPWARD_BIAS: f32 = 0.5;
 
 fn shoot_player(
     enemy_qy: Query<
         (Entity, &Transform),
-        (With<Enemy>, (With<ReadyToShoot>, With<EnemySeesPlayer>)),
+        (
+            With<Enemy>,
+            (With<ReadyToShoot>, With<EnemySeesPlayer>),
+            Without<Stunned>,
+        ),
     >,
     mut cooldown_qy: Query<&mut ShootCooldown>,
+    grenadier_qy: Query<&Grenadier>,
+    sniper_qy: Query<&Sniper>,
     player_qy: Query<&Transform, With<Player>>,
     mut commands: Commands,
     time: Res<Time>,
@@ -131,16 +181,43 @@ fn shoot_player(
             let dir_to_player = (player_transform.translation - enemy_transform.translation)
                 .truncate()
                 .normalize();
-            spawn_projectile(
-                &mut commands,
-                // vec3(enemy_transform.translation.x, enemy_transform.translation.y + 200., 0.),
-                enemy_transform.translation,
-                dir_to_player,
-                PROJECTILE_DEFAULT_VELOCITY,
-                PROJECTILE_DAMAGE,
-                PROJECTILE_DEFAULT_KNOCKBACK,
-                vec![enemy_entity],
-            );
+            if grenadier_qy.contains(enemy_entity) {
+                let throw_direction =
+                    (dir_to_player + Vec2::Y * GRENADE_THROW_UPWARD_BIAS).normalize();
+                let grenade_entity = spawn_grenade(
+                    &mut commands,
+                    enemy_transform.translation,
+                    throw_direction,
+                    GRENADE_EXPLOSIVE,
+                    GRENADE_FUSE,
+                    time.elapsed_secs(),
+                    vec![enemy_entity],
+                );
+                commands
+                    .entity(grenade_entity)
+                    .insert((InflictsStatusEffect(GRENADE_SLOW), ShotBy(enemy_entity)));
+            } else if sniper_qy.contains(enemy_entity) {
+                commands.trigger(HitscanEvent {
+                    shooter: enemy_entity,
+                    origin: enemy_transform.translation.truncate(),
+                    direction: dir_to_player,
+                    max_distance: HITSCAN_MAX_DISTANCE,
+                    damage: SNIPER_DAMAGE,
+                    knockback: PROJECTILE_DEFAULT_KNOCKBACK,
+                });
+            } else {
+                let projectile_entity = spawn_projectile(
+                    &mut commands,
+                    // vec3(enemy_transform.translation.x, enemy_transform.translation.y + 200., 0.),
+                    enemy_transform.translation,
+                    dir_to_player,
+                    PROJECTILE_DEFAULT_VELOCITY,
+                    PROJECTILE_DAMAGE,
+                    PROJECTILE_DEFAULT_KNOCKBACK,
+                    vec![enemy_entity],
+                );
+                commands.entity(projectile_entity).insert(ShotBy(enemy_entity));
+            }
             commands.entity(enemy_entity).remove::<ReadyToShoot>();
             // If the entity has a ShootCooldown component reset the cooldown start time
             if let Ok(mut cooldown) = cooldown_qy.get_mut(enemy_entity) {
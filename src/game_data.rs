@@ -2,6 +2,7 @@ use avian2d::prelude::*;
 use bevy::prelude::*;
 use bevy::reflect::Tuple;
 
+use crate::grounded::GroundDetector;
 use crate::level::*;
 use crate::player::*;
 pub struct GameDataPlugin;
@@ -50,9 +51,48 @@ pub enum LevelIdentifier {
 pub struct Defense(pub f32);
 #[derive(Component)]
 pub struct Health(pub f32);
+impl Health {
+    /// Lower health by `damage` without going below zero
+    pub fn take_damage(&mut self, damage: f32) {
+        self.0 = (self.0 - damage).max(0.);
+    }
+}
 #[derive(Component)]
 pub struct CanBeHitByProjectile;
 
+#[derive(PhysicsLayer, Default)]
+pub enum GameLayer {
+    #[default]
+    Default,
+    // Child hitbox colliders, kept out of line of sight and ground checks
+    Hitbox,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BodyPart {
+    Head,
+    Body,
+}
+
+/// Child sensor collider of a character that projectiles register hits against,
+/// the damage of a hit is scaled by `damage_multiplier`
+#[derive(Component, Clone, Copy)]
+pub struct Hitbox {
+    pub owner: Entity,
+    pub part: BodyPart,
+    pub damage_multiplier: f32,
+}
+
+/// Marks characters whose projectile hits are registered by their child `Hitbox`es
+/// instead of their own collider
+#[derive(Component)]
+pub struct UsesHitboxes;
+
+// Fraction of the character height that counts as the head
+const HEAD_HITBOX_HEIGHT_FRACTION: f32 = 0.25;
+const HEAD_DAMAGE_MULTIPLIER: f32 = 2.;
+const BODY_DAMAGE_MULTIPLIER: f32 = 1.;
+
 pub const PROJECTILE_DEFAULT_VELOCITY: f32 = 1_000.;
 pub const PROJECTILE_DEFAULT_KNOCKBACK: f32 = 100_000.;
 
@@ -90,22 +130,65 @@ pub fn spawn_character(
     if let Some(custom_sprite) = bundle.custom_sprite {
         sprite = custom_sprite;
     }
-    commands
-        .spawn((
-            GameEntity::LevelEntity,
-            GameCharacter,
-            FacingDirection::default(),
+    let mut character = commands.spawn((
+        GameEntity::LevelEntity,
+        GameCharacter,
+        GroundDetector,
+        FacingDirection::default(),
+        CanBeHitByProjectile,
+        UsesHitboxes,
+        sprite,
+        RigidBody::Dynamic,
+        Mass(800.),
+        LinearVelocity::ZERO,
+        LockedAxes::ROTATION_LOCKED,
+        Transform::from_xyz(bundle.position.x, bundle.position.y, bundle.position.z),
+        Collider::rectangle(bundle.size.x, bundle.size.y),
+        additional_components,
+    ));
+    let owner = character.id();
+    // Head on top and body below it, together they cover the whole character
+    character.with_children(|parent| {
+        parent.spawn((
+            Hitbox {
+                owner,
+                part: BodyPart::Head,
+                damage_multiplier: HEAD_DAMAGE_MULTIPLIER,
+            },
             CanBeHitByProjectile,
-            sprite,
-            RigidBody::Dynamic,
-            Mass(800.),
-            LinearVelocity::ZERO,
-            LockedAxes::ROTATION_LOCKED,
-            Transform::from_xyz(bundle.position.x, bundle.position.y, bundle.position.z),
-            Collider::rectangle(bundle.size.x, bundle.size.y),
-            additional_components,
-        ))
-        .id()
+            Sensor,
+            CollisionLayers::new(GameLayer::Hitbox, LayerMask::ALL),
+            hitbox_shape(BodyPart::Head, bundle.size),
+        ));
+        parent.spawn((
+            Hitbox {
+                owner,
+                part: BodyPart::Body,
+                damage_multiplier: BODY_DAMAGE_MULTIPLIER,
+            },
+            CanBeHitByProjectile,
+            Sensor,
+            CollisionLayers::new(GameLayer::Hitbox, LayerMask::ALL),
+            hitbox_shape(BodyPart::Body, bundle.size),
+        ));
+    });
+    owner
+}
+
+/// Collider and offset of the `part` hitbox of a character of `size`
+pub fn hitbox_shape(part: BodyPart, size: Vec2) -> (Collider, Transform) {
+    let head_height = size.y * HEAD_HITBOX_HEIGHT_FRACTION;
+    let body_height = size.y - head_height;
+    match part {
+        BodyPart::Head => (
+            Collider::rectangle(size.x, head_height),
+            Transform::from_xyz(0., (size.y - head_height) / 2., 0.),
+        ),
+        BodyPart::Body => (
+            Collider::rectangle(size.x, body_height),
+            Transform::from_xyz(0., -head_height / 2., 0.),
+        ),
+    }
 }
 
 #[derive(Default)]
@@ -124,6 +207,10 @@ impl GroundSpawnData {
             y2: y2 as f32,
         }
     }
+
+    pub fn center(&self) -> Vec2 {
+        vec2(avg([self.x1, self.x2]), avg([self.y1, self.y2]))
+    }
 }
 
 
@@ -132,11 +219,11 @@ pub fn spawn_ground(
     commands: &mut Commands,
     image: Handle<Image>,
     ground_spawn_data: GroundSpawnData,
-) {
+) -> Entity {
     let GroundSpawnData { x1, x2, y1, y2 } = ground_spawn_data;
     commands.spawn((
         GameEntity::LevelEntity,
-        Ground,
+        Ground::Solid,
         CanBeHitByProjectile,
         Sprite {
             custom_size: Some(Vec2::new(x2 - x1, y2 - y1)),
@@ -147,7 +234,7 @@ pub fn spawn_ground(
         RigidBody::Static,
         Transform::from_xyz(avg([x1, x2]), avg([y1, y2]), 0.),
         Collider::rectangle(x2 - x1, y2 - y1),
-    ));
+    )).id()
 }
 
 fn avg<T, I>(iter: I) -> f32
@@ -0,0 +1,120 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::game_data::*;
+use crate::platforms::{DroppingThrough, is_on_top_of_platform};
+use crate::player::Ground;
+
+// How far below its collider ground is looked for
+const GROUND_DETECTION_DISTANCE: f32 = 2.;
+// The probe is this much smaller on each side, so ground the entity is resting on still counts
+// as a hit instead of as already overlapping, and walls it's pressed against don't count at all
+const GROUND_DETECTION_SKIN: f32 = 1.;
+
+pub struct GroundedPlugin;
+
+impl Plugin for GroundedPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            detect_ground.run_if(in_state(GameState::PlayingLevel)),
+        );
+    }
+}
+
+/// Looks for ground below the entity every step, keeping `Grounded` up to date
+#[derive(Component, Default)]
+pub struct GroundDetector;
+
+/// Standing on `ground` while not moving up
+#[derive(Component)]
+pub struct Grounded {
+    pub ground: Entity,
+}
+
+/// The entity touched down on ground after being in the air
+#[derive(EntityEvent)]
+pub struct Landed {
+    pub entity: Entity,
+}
+
+/// The entity jumped, fell or was knocked off the ground it was standing on
+#[derive(EntityEvent)]
+pub struct LeftGround {
+    pub entity: Entity,
+}
+
+fn detect_ground(
+    detector_qy: Query<
+        (
+            Entity,
+            &Transform,
+            &Collider,
+            &LinearVelocity,
+            Option<&Grounded>,
+            Has<DroppingThrough>,
+        ),
+        With<GroundDetector>,
+    >,
+    ground_qy: Query<&Ground>,
+    aabb_qy: Query<&ColliderAabb>,
+    spatial_query: SpatialQuery,
+    mut commands: Commands,
+) {
+    for (entity, transform, collider, velocity, grounded_opt, is_dropping_through) in
+        detector_qy.iter()
+    {
+        let position = transform.translation.truncate();
+        let aabb = collider.aabb(position, 0.);
+        // Shape cast down so only ground below the entity counts, not the sides of pillars
+        let ground = if velocity.y > 0. {
+            None
+        } else {
+            let size = aabb.size();
+            let probe = Collider::rectangle(
+                size.x - 2. * GROUND_DETECTION_SKIN,
+                size.y - 2. * GROUND_DETECTION_SKIN,
+            );
+            let filter =
+                SpatialQueryFilter::from_excluded_entities([entity]).with_mask(GameLayer::Default);
+            spatial_query
+                .cast_shape_predicate(
+                    &probe,
+                    position,
+                    0.,
+                    Dir2::NEG_Y,
+                    &ShapeCastConfig::from_max_distance(
+                        GROUND_DETECTION_SKIN + GROUND_DETECTION_DISTANCE,
+                    ),
+                    &filter,
+                    // One-way platforms only hold up what's on top of them
+                    &|hit_entity| match ground_qy.get(hit_entity) {
+                        Ok(Ground::Solid) => true,
+                        Ok(Ground::OneWay) => {
+                            !is_dropping_through
+                                && aabb_qy.get(hit_entity).is_ok_and(|platform_aabb| {
+                                    is_on_top_of_platform(&aabb, platform_aabb)
+                                })
+                        }
+                        Err(_) => false,
+                    },
+                )
+                .map(|hit| hit.entity)
+        };
+        match (ground, grounded_opt) {
+            (Some(ground), None) => {
+                commands.entity(entity).insert(Grounded { ground });
+                commands.trigger(Landed { entity });
+            }
+            // Stepped from one piece of ground onto another
+            (Some(ground), Some(grounded)) if grounded.ground != ground => {
+                commands.entity(entity).insert(Grounded { ground });
+            }
+            (None, Some(_)) => {
+                commands.entity(entity).remove::<Grounded>();
+                commands.trigger(LeftGround { entity });
+            }
+            _ => {}
+        }
+    }
+}
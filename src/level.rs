@@ -6,7 +6,9 @@ use crate::enemy::*;
 use crate::game_data::*;
 use crate::loading::*;
 use crate::main_menu::*;
+use crate::platforms::spawn_one_way_platform;
 use crate::player::*;
+use crate::props::*;
 
 pub struct LevelPlugin;
 impl Plugin for LevelPlugin {
@@ -394,6 +396,8 @@ pub fn ev_load_level_entities(
 }
 
 const PLAYER_IMAGE_PATH: &str = "Player.png";
+const HUD_FONT_SIZE: f32 = 33.;
+const HUD_TEXT_COLOR: Color = Color::srgb(0.9, 0.9, 0.9);
 const PLAYER_SHOOT_COOLDOWN: f32 = 0.7;
 
 pub fn load_level_entities(
@@ -426,6 +430,9 @@ pub fn load_level_entities(
                         Player,
                         CanDash,
                         CanGrapple,
+                        CanGroundPound,
+                        CanParry,
+                        CanBlink,
                         Health(100.),
                         ShootCooldown {
                             cooldown: PLAYER_SHOOT_COOLDOWN,
@@ -450,6 +457,9 @@ pub fn load_level_entities(
                     asset_server.load("metal_box_small.png"),
                     GroundSpawnData::new(-5000, 5000, -150, -50),
                 );
+                // Crates to yank around with the grapple
+                spawn_crate(commands, asset_server.load("metal_box.png"), vec2(-300., 0.), 60.);
+                spawn_crate(commands, asset_server.load("metal_box.png"), vec2(850., 0.), 60.);
                 // Spawn enemies
                 spawn_character(
                     commands,
@@ -493,6 +503,30 @@ pub fn load_level_entities(
                         },
                     ),
                 );
+                // Sniper guarding the target from a distance
+                spawn_character(
+                    commands,
+                    CharacterBundle {
+                        size: vec2(character_width, character_height),
+                        position: vec3(1600., 700., 0.),
+                        color: Color::srgb(8.0, 0.0, 0.0),
+                        custom_sprite: Some(Sprite {
+                            custom_size: Some(vec2(character_width, character_height)),
+                            image: asset_server.load("Enemy.png"),
+                            color: Color::srgb(0.6, 0.6, 1.0),
+                            ..default()
+                        }),
+                    },
+                    (
+                        Enemy,
+                        Sniper,
+                        Health(100.),
+                        ShootCooldown {
+                            cooldown: 3.,
+                            cooldown_start: None,
+                        },
+                    ),
+                );
                 spawn_character(
                     commands,
                     CharacterBundle {
@@ -515,56 +549,7 @@ pub fn load_level_entities(
                         },
                     ),
                 );
-                // Spawn Player UI
-                commands.spawn((
-                    GameEntity::LevelEntity,
-                    Node {
-                        width: Val::Percent(100.0),
-                        height: Val::Percent(100.0),
-                        align_items: AlignItems::End,
-                        align_content: AlignContent::SpaceAround,
-                        justify_content: JustifyContent::Start,
-                        flex_direction: FlexDirection::Row,
-                        ..default()
-                    },
-                    children![(
-                        Node {
-                            width: Val::Auto,
-                            height: Val::Auto,
-                            padding: UiRect::all(Val::Px(10.)),
-                            border: UiRect::all(Val::Px(5.0)),
-                            // horizontally center child text
-                            justify_content: JustifyContent::Center,
-                            // vertically center child text
-                            align_items: AlignItems::Center,
-                            ..default()
-                        },
-                        // BorderColor::all(Color::WHITE),
-                        // BorderRadius::MAX,
-                        // BackgroundColor(Color::BLACK),
-                        children![
-                            (
-                                Text::new("Health: "),
-                                TextFont {
-                                    font_size: 33.0,
-                                    ..default()
-                                },
-                                TextColor(Color::srgb(0.9, 0.9, 0.9)),
-                                TextShadow::default(),
-                            ),
-                            (
-                                PlayerHealthUi,
-                                Text::new(""),
-                                TextFont {
-                                    font_size: 33.0,
-                                    ..default()
-                                },
-                                TextColor(Color::srgb(0.9, 0.9, 0.9)),
-                                TextShadow::default(),
-                            )
-                        ],
-                    )],
-                ));
+                spawn_player_hud(commands);
             } else if id == 2 {
                 let character_width = 60.;
                 let character_height = 100.;
@@ -586,6 +571,9 @@ pub fn load_level_entities(
                         Player,
                         CanDash,
                         CanGrapple,
+                        CanGroundPound,
+                        CanParry,
+                        CanBlink,
                         Health(100.),
                         ShootCooldown {
                             cooldown: PLAYER_SHOOT_COOLDOWN,
@@ -604,14 +592,14 @@ pub fn load_level_entities(
                 );
 
                 // Mid-level platform 1
-                spawn_ground(
+                spawn_one_way_platform(
                     commands,
                     asset_server.load("metal_box_small.png"),
                     GroundSpawnData::new(50, 450, 85, 115),
                 );
 
                 // Mid-level platform 2
-                spawn_ground(
+                spawn_one_way_platform(
                     commands,
                     asset_server.load("metal_box_small.png"),
                     GroundSpawnData::new(550, 850, 235, 265),
@@ -631,6 +619,24 @@ pub fn load_level_entities(
                     GroundSpawnData::new(425, 475, -300, 300),
                 );
 
+                // Breakable platform that can be yanked down
+                spawn_breakable_platform(
+                    commands,
+                    asset_server.load("metal_box_small.png"),
+                    GroundSpawnData::new(-400, -200, 150, 180),
+                );
+
+                // Moving platform to swing from
+                spawn_moving_platform(
+                    commands,
+                    asset_server.load("metal_box_small.png"),
+                    GroundSpawnData::new(900, 1050, 450, 475),
+                    vec2(400., 0.),
+                    150.,
+                );
+
+                spawn_crate(commands, asset_server.load("metal_box.png"), vec2(-600., -100.), 60.);
+
                 // Spawn enemies with varied positions
                 // Ground level enemies
                 spawn_character(
@@ -678,6 +684,61 @@ pub fn load_level_entities(
                     ),
                 );
 
+                // Shield bearer, the shield has to be yanked off before it can be shot
+                let shield_bearer = spawn_character(
+                    commands,
+                    CharacterBundle {
+                        size: vec2(character_width, character_height),
+                        position: vec3(200., 300., 0.),
+                        color: Color::srgb(8.0, 0.0, 0.0),
+                        custom_sprite: Some(Sprite {
+                            custom_size: Some(vec2(character_width, character_height)),
+                            image: asset_server.load("Enemy.png"),
+                            ..default()
+                        }),
+                    },
+                    (
+                        Enemy,
+                        Health(100.),
+                        ShootCooldown {
+                            cooldown: 1.5,
+                            cooldown_start: None,
+                        },
+                    ),
+                );
+                spawn_shield(
+                    commands,
+                    shield_bearer,
+                    // Short of the feet so it doesn't prop the bearer up off the ground
+                    vec2(15., character_height * 0.9),
+                    character_width * 0.75,
+                );
+
+                // Grenadier on mid-level platform 2
+                spawn_character(
+                    commands,
+                    CharacterBundle {
+                        size: vec2(character_width, character_height),
+                        position: vec3(700., 400., 0.),
+                        color: Color::srgb(8.0, 0.0, 0.0),
+                        custom_sprite: Some(Sprite {
+                            custom_size: Some(vec2(character_width, character_height)),
+                            image: asset_server.load("Enemy.png"),
+                            color: Color::srgb(0.6, 1.0, 0.6),
+                            ..default()
+                        }),
+                    },
+                    (
+                        Enemy,
+                        Grenadier,
+                        Health(100.),
+                        ShootCooldown {
+                            cooldown: 2.5,
+                            cooldown_start: None,
+                        },
+                    ),
+                );
+
                 // High platform bounty target
                 spawn_character(
                     commands,
@@ -702,52 +763,66 @@ pub fn load_level_entities(
                     ),
                 );
 
-                // Spawn Player UI
-                commands.spawn((
-                    GameEntity::LevelEntity,
-                    Node {
-                        width: Val::Percent(100.0),
-                        height: Val::Percent(100.0),
-                        align_items: AlignItems::End,
-                        align_content: AlignContent::SpaceAround,
-                        justify_content: JustifyContent::Start,
-                        flex_direction: FlexDirection::Row,
-                        ..default()
-                    },
-                    children![(
-                        Node {
-                            width: Val::Auto,
-                            height: Val::Auto,
-                            padding: UiRect::all(Val::Px(10.)),
-                            border: UiRect::all(Val::Px(5.0)),
-                            justify_content: JustifyContent::Center,
-                            align_items: AlignItems::Center,
-                            ..default()
-                        },
-                        children![
-                            (
-                                Text::new("Health: "),
-                                TextFont {
-                                    font_size: 33.0,
-                                    ..default()
-                                },
-                                TextColor(Color::srgb(0.9, 0.9, 0.9)),
-                                TextShadow::default(),
-                            ),
-                            (
-                                PlayerHealthUi,
-                                Text::new(""),
-                                TextFont {
-                                    font_size: 33.0,
-                                    ..default()
-                                },
-                                TextColor(Color::srgb(0.9, 0.9, 0.9)),
-                                TextShadow::default(),
-                            )
-                        ],
-                    )],
-                ));
+                spawn_player_hud(commands);
             }
         }
     }
 }
+
+fn spawn_player_hud(commands: &mut Commands) {
+    commands.spawn((
+        GameEntity::LevelEntity,
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            align_items: AlignItems::End,
+            align_content: AlignContent::SpaceAround,
+            justify_content: JustifyContent::Start,
+            flex_direction: FlexDirection::Row,
+            ..default()
+        },
+        children![
+            (
+                hud_box_node(),
+                children![
+                    hud_text("Health: "),
+                    (PlayerHealthUi, hud_text("")),
+                ],
+            ),
+            // Filled with the player's loadout once it's given
+            (
+                AbilityHudRoot,
+                Node {
+                    flex_direction: FlexDirection::Row,
+                    ..default()
+                },
+            ),
+        ],
+    ));
+}
+
+fn hud_box_node() -> Node {
+    Node {
+        width: Val::Auto,
+        height: Val::Auto,
+        padding: UiRect::all(Val::Px(10.)),
+        border: UiRect::all(Val::Px(5.0)),
+        // horizontally center child text
+        justify_content: JustifyContent::Center,
+        // vertically center child text
+        align_items: AlignItems::Center,
+        ..default()
+    }
+}
+
+fn hud_text(text: &str) -> impl Bundle {
+    (
+        Text::new(text),
+        TextFont {
+            font_size: HUD_FONT_SIZE,
+            ..default()
+        },
+        TextColor(HUD_TEXT_COLOR),
+        TextShadow::default(),
+    )
+}
@@ -17,12 +17,23 @@ use loading::*;
 mod game_data;
 use game_data::*;
 mod enemy;
+mod grounded;
+mod platforms;
 mod projectiles;
+mod props;
+mod status_effects;
 
 mod abilities;
 use abilities::AbilitiesPlugin;
 
-use crate::{enemy::EnemyPlugin, projectiles::ProjectilesPlugin};
+use crate::{
+    enemy::EnemyPlugin,
+    grounded::GroundedPlugin,
+    platforms::{OneWayPlatformHooks, PlatformsPlugin},
+    projectiles::ProjectilesPlugin,
+    props::PropsPlugin,
+    status_effects::StatusEffectsPlugin,
+};
 
 fn main() {
     App::new()
@@ -39,7 +50,9 @@ fn main() {
             bevy_framepace::FramepacePlugin,
             // EguiPlugin::default(),
             // WorldInspectorPlugin::default(),
-            PhysicsPlugins::default().set(PhysicsInterpolationPlugin::interpolate_all()),
+            PhysicsPlugins::default()
+                .with_collision_hooks::<OneWayPlatformHooks>()
+                .set(PhysicsInterpolationPlugin::interpolate_all()),
         ))
         // .insert_resource(FramepaceSettings{ limiter: Limiter::from_framerate(144.)})
         .insert_resource(Gravity(Vec2::NEG_Y * 3000.0))
@@ -53,6 +66,10 @@ fn main() {
             AbilitiesPlugin,
             EnemyPlugin,
             ProjectilesPlugin,
+            StatusEffectsPlugin,
+            PropsPlugin,
+            GroundedPlugin,
+            PlatformsPlugin,
         ))
         .add_systems(Startup, startup)
         .add_systems(Update, update)
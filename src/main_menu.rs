@@ -1,5 +1,6 @@
 use bevy::prelude::*;
 
+use crate::abilities::*;
 use crate::game_data::*;
 use crate::level::LatestUnlockedLevel;
 use crate::loading::*;
@@ -17,7 +18,8 @@ impl Plugin for MainMenuPlugin {
         app.add_systems(Update, grow_on_hover)
             .add_systems(
                 Update,
-                main_menu_buttons.run_if(in_state(GameState::MainMenu)),
+                (main_menu_buttons, loadout_toggle_buttons, show_selected_loadout)
+                    .run_if(in_state(GameState::MainMenu)),
             )
             .add_observer(load_main_menu_entities);
     }
@@ -48,6 +50,43 @@ fn main_menu_buttons(
     }
 }
 
+const LOADOUT_SELECTED_COLOR: Color = Color::srgb(0.1, 0.4, 0.1);
+
+/// Picks or unpicks an ability for the loadout taken into levels
+#[derive(Component)]
+struct LoadoutToggleButton(AbilityId);
+
+fn loadout_toggle_buttons(
+    toggle_button_qy: Query<
+        (&Interaction, &LoadoutToggleButton),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut selected_loadout: ResMut<SelectedLoadout>,
+) {
+    for (interaction, toggle_button) in toggle_button_qy.iter() {
+        if let Interaction::Pressed = interaction {
+            selected_loadout.toggle(toggle_button.0);
+        }
+    }
+}
+
+fn show_selected_loadout(
+    mut toggle_button_qy: Query<(&LoadoutToggleButton, &mut BackgroundColor)>,
+    added_toggle_button_qy: Query<(), Added<LoadoutToggleButton>>,
+    selected_loadout: Res<SelectedLoadout>,
+) {
+    if !selected_loadout.is_changed() && added_toggle_button_qy.is_empty() {
+        return;
+    }
+    for (toggle_button, mut background_color) in toggle_button_qy.iter_mut() {
+        background_color.0 = if selected_loadout.0.contains(&toggle_button.0) {
+            LOADOUT_SELECTED_COLOR
+        } else {
+            Color::BLACK
+        };
+    }
+}
+
 fn grow_on_hover(
     mut interaction_query: Query<
         (&Interaction, &mut UiTransform),
@@ -80,6 +119,7 @@ pub fn load_main_menu_entities(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     latest_unlocked_level: Res<LatestUnlockedLevel>,
+    ability_registry: Res<AbilityRegistry>,
 ) {
     
 
@@ -189,6 +229,58 @@ pub fn load_main_menu_entities(
         }))),
     ));
 
+    // Loadout picker at the bottom of the screen
+    let loadout_root = commands
+        .spawn((
+            GameEntity::MainMenuEntity,
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::End,
+                justify_content: JustifyContent::Center,
+                column_gap: Val::Px(10.),
+                padding: UiRect::all(Val::Px(20.)),
+                ..default()
+            },
+            Name::new("Loadout picker"),
+        ))
+        .id();
+    commands.entity(loadout_root).with_children(|parent| {
+        parent.spawn((
+            Text::new(format!("Loadout (pick up to {}):", MAX_LOADOUT_SIZE)),
+            TextFont {
+                font_size: 25.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.9, 0.9, 0.9)),
+        ));
+        for (ability, definition) in ability_registry.iter() {
+            parent.spawn((
+                GrowOnHover,
+                LoadoutToggleButton(ability),
+                Button,
+                Node {
+                    padding: UiRect::all(Val::Px(10.)),
+                    border: UiRect::all(Val::Px(3.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                BorderColor::all(Color::WHITE),
+                BorderRadius::MAX,
+                BackgroundColor(Color::BLACK),
+                children![(
+                    Text::new(definition.name),
+                    TextFont {
+                        font_size: 25.0,
+                        ..default()
+                    },
+                    TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                )],
+            ));
+        }
+    });
+
     // commands.spawn((
     //     GameEntity::MainMenuEntity,
     //     Node {
@@ -0,0 +1,94 @@
+use avian2d::prelude::*;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+use crate::game_data::*;
+use crate::player::Ground;
+
+// How far something can sink into the top of a one-way platform and still be standing on it
+const ONE_WAY_PLATFORM_TOLERANCE: f32 = 5.;
+// Seconds a drop through one-way platforms lasts
+pub const DROP_THROUGH_TIME: f32 = 0.25;
+
+pub struct PlatformsPlugin;
+
+impl Plugin for PlatformsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            end_drop_through.run_if(in_state(GameState::PlayingLevel)),
+        );
+    }
+}
+
+/// Platform that can be jumped up through from below and dropped down through
+pub fn spawn_one_way_platform(
+    commands: &mut Commands,
+    image: Handle<Image>,
+    ground_spawn_data: GroundSpawnData,
+) -> Entity {
+    let platform_entity = spawn_ground(commands, image, ground_spawn_data);
+    commands
+        .entity(platform_entity)
+        .insert((Ground::OneWay, ActiveCollisionHooks::MODIFY_CONTACTS));
+    platform_entity
+}
+
+/// Falls through one-way platforms until `until`
+#[derive(Component)]
+pub struct DroppingThrough {
+    pub until: f32,
+}
+
+fn end_drop_through(
+    dropping_qy: Query<(Entity, &DroppingThrough)>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, dropping_through) in dropping_qy.iter() {
+        if time.elapsed_secs() >= dropping_through.until {
+            commands.entity(entity).remove::<DroppingThrough>();
+        }
+    }
+}
+
+/// Whether something covering `aabb` is on top of a one-way platform covering `platform_aabb`
+pub fn is_on_top_of_platform(aabb: &ColliderAabb, platform_aabb: &ColliderAabb) -> bool {
+    aabb.min.y >= platform_aabb.max.y - ONE_WAY_PLATFORM_TOLERANCE
+}
+
+/// One-way platforms only collide with what's on top of them and isn't dropping through
+#[derive(SystemParam)]
+pub struct OneWayPlatformHooks<'w, 's> {
+    ground_qy: Query<'w, 's, &'static Ground>,
+    aabb_qy: Query<'w, 's, &'static ColliderAabb>,
+    dropping_through_qy: Query<'w, 's, (), With<DroppingThrough>>,
+}
+
+impl OneWayPlatformHooks<'_, '_> {
+    fn is_one_way_platform(&self, entity: Entity) -> bool {
+        matches!(self.ground_qy.get(entity), Ok(Ground::OneWay))
+    }
+
+    fn platform_collides_with(&self, platform: Entity, other: Entity) -> bool {
+        if self.dropping_through_qy.contains(other) {
+            return false;
+        }
+        match (self.aabb_qy.get(other), self.aabb_qy.get(platform)) {
+            (Ok(other_aabb), Ok(platform_aabb)) => is_on_top_of_platform(other_aabb, platform_aabb),
+            _ => true,
+        }
+    }
+}
+
+impl CollisionHooks for OneWayPlatformHooks<'_, '_> {
+    fn modify_contacts(&self, contacts: &mut ContactPair, _commands: &mut Commands) -> bool {
+        if self.is_one_way_platform(contacts.collider1) {
+            self.platform_collides_with(contacts.collider1, contacts.collider2)
+        } else if self.is_one_way_platform(contacts.collider2) {
+            self.platform_collides_with(contacts.collider2, contacts.collider1)
+        } else {
+            true
+        }
+    }
+}
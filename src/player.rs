@@ -1,13 +1,18 @@
 use avian2d::prelude::*;
 use bevy::ecs::relationship::RelationshipSourceCollection;
+use bevy::input::mouse::{AccumulatedMouseScroll, MouseScrollUnit};
 use bevy::prelude::*;
 use bevy::sprite;
 
 use crate::abilities::*;
 use crate::enemy::*;
 use crate::game_data::*;
+use crate::grounded::*;
+use crate::platforms::{DROP_THROUGH_TIME, DroppingThrough};
 use crate::level::FacingDirection;
 use crate::projectiles::*;
+use crate::props::Prop;
+use crate::status_effects::*;
 
 pub struct PlayerPlugin;
 
@@ -18,15 +23,17 @@ impl Plugin for PlayerPlugin {
                 Update,
                 (
                     player_movement,
+                    end_coyote_time,
+                    (player_crouch, player_slide).chain(),
+                    (grab_ledges, ledge_hang_input, climb_up).chain(),
                     camera_movement,
-                    right_click_start_position_system,
-                    right_click_end_position_system,
-                    grapple_input_system,
-                    end_grapple_input,
+                    draw_dash_preview,
+                    grapple_reel_input,
                     player_shoot_input,
                     player_health_ui,
+                    draw_grapple_reticle,
                     look_in_walk_direction,
-                    reset_jumps_on_ground,
+                    (detect_walls, wall_slide).chain(),
                     player_die,
                     (walking_animation, animate_jump).chain(),
                 )
@@ -35,12 +42,14 @@ impl Plugin for PlayerPlugin {
             .add_observer(player_shoot_event)
             .add_observer(start_jump_animation)
             .add_observer(landed_from_jumping)
+            .add_observer(reset_jumps_on_landing)
+            .add_observer(start_coyote_time)
             // Add this observer to fan out Swinging/PullingEnemy
-            .init_resource::<RightClickStartPostion>()
             .init_resource::<MovementModifiers>()
             .register_type::<MovementModifiers>()
             .insert_resource(MovementModifiers::default())
-            .init_resource::<GrappleKeybind>()
+            .init_resource::<GunModifiers>()
+            .register_type::<GunModifiers>()
             .init_resource::<GrapplingHookConfig>();
     }
 }
@@ -71,51 +80,204 @@ fn player_health_ui(
     }
 }
 
+/// Converts the cursor position in `window` to world space for a camera without zoom
+pub fn cursor_world_position(window: &Window, camera_transform: &Transform) -> Option<Vec2> {
+    // try to get raw mouse window position
+    let mouse_window_pos = window.cursor_position()?;
+    Some(window_to_world_position(window, camera_transform, mouse_window_pos))
+}
+
+/// Converts a position in `window` to world space for a camera without zoom
+pub fn window_to_world_position(
+    window: &Window,
+    camera_transform: &Transform,
+    window_pos: Vec2,
+) -> Vec2 {
+    // invert y
+    let window_pos = vec2(window_pos.x, window.height() - window_pos.y);
+    // Convert camera position to Vec2 using truncate()
+    let camera_pos = camera_transform.translation.truncate();
+    // Calculate world position (accounting for centered origin)
+    let window_size = Vec2::new(window.width(), window.height());
+    window_pos - window_size / 2.0 + camera_pos
+}
+
+const RETICLE_RADIUS: f32 = 12.;
+const RETICLE_WORLD_COLOR: Color = Color::srgb(0.3, 1.0, 0.3);
+const RETICLE_ENEMY_COLOR: Color = Color::srgb(1.0, 0.3, 0.3);
+const RETICLE_UNAVAILABLE_COLOR: Color = Color::srgb(0.5, 0.5, 0.5);
+
+/// Reticle at the cursor showing what the grappling hook would attach to when fired
+fn draw_grapple_reticle(
+    mut gizmos: Gizmos,
+    spatial_query: SpatialQuery,
+    player_qy: Query<
+        (
+            Entity,
+            &Transform,
+            &AbilityLoadout,
+            Option<&AbilityCharges>,
+            Has<Stunned>,
+        ),
+        (With<Player>, With<CanGrapple>, Without<Grappling>),
+    >,
+    window_qy: Query<&Window>,
+    camera_transform_qy: Query<&Transform, With<Camera>>,
+    ground_qy: Query<(), Or<(With<Ground>, With<Prop>)>>,
+    enemy_qy: Query<(), With<Enemy>>,
+    grappling_hook_config: Res<GrapplingHookConfig>,
+    ability_registry: Res<AbilityRegistry>,
+) {
+    let (Ok(window), Ok(camera_transform)) = (window_qy.single(), camera_transform_qy.single())
+    else {
+        return;
+    };
+    let Some(mouse_world_pos) = cursor_world_position(window, camera_transform) else {
+        return;
+    };
+    for (entity, transform, loadout, ability_charges_opt, is_stunned) in player_qy.iter() {
+        // Only shown when the grapple was brought into the level
+        if loadout.slot(GRAPPLE_ABILITY).is_none() {
+            continue;
+        }
+        let player_pos = transform.translation.truncate();
+        let Ok(direction) = Dir2::new(mouse_world_pos - player_pos) else {
+            continue;
+        };
+        let on_cooldown = ability_charges_opt.is_some_and(|ability_charges| {
+            ability_charges.charges(GRAPPLE_ABILITY, &ability_registry) == 0
+        });
+        let filter =
+            SpatialQueryFilter::from_excluded_entities([entity]).with_mask(GameLayer::Default);
+        let hit = spatial_query.cast_ray_predicate(
+            player_pos,
+            direction,
+            grappling_hook_config.max_range,
+            true,
+            &filter,
+            &|hit_entity| ground_qy.contains(hit_entity) || enemy_qy.contains(hit_entity),
+        );
+
+        let color = match hit {
+            _ if on_cooldown || is_stunned => RETICLE_UNAVAILABLE_COLOR,
+            Some(hit_data) if enemy_qy.contains(hit_data.entity) => RETICLE_ENEMY_COLOR,
+            Some(_) => RETICLE_WORLD_COLOR,
+            // Out of range
+            None => RETICLE_UNAVAILABLE_COLOR,
+        };
+        gizmos.circle_2d(mouse_world_pos, RETICLE_RADIUS, color);
+        match hit {
+            // Mark where the hook would land when something is in the way of the cursor
+            Some(hit_data) => {
+                let hit_point = player_pos + direction.as_vec2() * hit_data.distance;
+                gizmos.circle_2d(hit_point, RETICLE_RADIUS / 2., color);
+            }
+            None => {
+                // Cross the reticle out when out of range
+                let diagonal = Vec2::splat(RETICLE_RADIUS * std::f32::consts::FRAC_1_SQRT_2);
+                gizmos.line_2d(mouse_world_pos - diagonal, mouse_world_pos + diagonal, color);
+            }
+        }
+    }
+}
+
 const PLAYER_PROJECTILE_DAMAGE: f32 = 20.;
+const PLAYER_RAILGUN_DAMAGE: f32 = 50.;
+const PLAYER_ROCKET_BURN: StatusEffect = StatusEffect {
+    kind: StatusEffectKind::Burn,
+    duration: 3.,
+    magnitude: 5.,
+};
+const PLAYER_BULLET_BLEED: StatusEffect = StatusEffect {
+    kind: StatusEffectKind::Bleed,
+    duration: 2.,
+    magnitude: 3.,
+};
+const PLAYER_ROCKET_EXPLOSIVE: Explosive = Explosive {
+    radius: 200.,
+    damage: 40.,
+    knockback: 400_000.,
+    explode_on_impact: true,
+};
+
+#[derive(Reflect, Resource)]
+#[reflect(Resource)]
+pub struct GunModifiers {
+    // Times a bullet bounces off ground before despawning, 0 for no ricochet
+    pub ricochet_bounces: u8,
+    // Characters a bullet passes through before despawning, 0 for no piercing
+    pub pierce_count: u8,
+}
+impl Default for GunModifiers {
+    fn default() -> Self {
+        GunModifiers {
+            ricochet_bounces: 0,
+            pierce_count: 0,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum PlayerWeapon {
+    Gun,
+    RocketLauncher,
+    Railgun,
+}
 
 #[derive(Event)]
-struct PlayerShootEvent;
+struct PlayerShootEvent {
+    weapon: PlayerWeapon,
+}
 
-fn player_shoot_input(mouse_input: Res<ButtonInput<MouseButton>>, mut commands: Commands) {
+fn player_shoot_input(
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut commands: Commands,
+) {
     if mouse_input.just_released(MouseButton::Left) {
-        commands.trigger(PlayerShootEvent);
+        commands.trigger(PlayerShootEvent {
+            weapon: PlayerWeapon::Gun,
+        });
+    }
+    if mouse_input.just_released(MouseButton::Middle) {
+        commands.trigger(PlayerShootEvent {
+            weapon: PlayerWeapon::RocketLauncher,
+        });
+    }
+    if keyboard_input.just_pressed(KeyCode::KeyE) {
+        commands.trigger(PlayerShootEvent {
+            weapon: PlayerWeapon::Railgun,
+        });
     }
 }
 
 fn player_shoot_event(
-    _shoot_event: On<PlayerShootEvent>,
-    player_qy: Query<(Entity, &Transform), With<Player>>,
+    shoot_event: On<PlayerShootEvent>,
+    player_qy: Query<(Entity, &Transform), (With<Player>, Without<Stunned>)>,
     window_qy: Query<&Window>,
     camera_transform_qy: Query<(&Transform), With<Camera>>,
     mut commands: Commands,
     mut shoot_cooldown_qy: Query<&mut ShootCooldown, With<Player>>,
+    gun_modifiers: Res<GunModifiers>,
+    bullet_time: Res<BulletTime>,
     time: Res<Time>,
 ) {
     // get window
     let window = window_qy
         .single()
         .expect("Multiple Windows present, not compatible with current grapple implementation");
-    // try to get raw mouse window position
-    if let Some(mouse_window_pos) = window.cursor_position() {
-        // invert y
-        let mouse_window_pos = vec2(mouse_window_pos.x, window.height() - mouse_window_pos.y);
-        // get camera transform
-        let camera_transform = camera_transform_qy
-            .single()
-            .expect("Found multiple cameras, incompatible with current grapple implementation");
-
-        // Convert camera position to Vec2 using truncate()
-        let camera_pos = camera_transform.translation.truncate();
-
-        // Calculate mouse world position (accounting for centered origin)
-        let window_size = Vec2::new(window.width(), window.height());
-        let mouse_world_pos = mouse_window_pos - window_size / 2.0 + camera_pos;
-
+    // get camera transform
+    let camera_transform = camera_transform_qy
+        .single()
+        .expect("Found multiple cameras, incompatible with current grapple implementation");
+    if let Some(mouse_world_pos) = cursor_world_position(window, camera_transform) {
         for (entity, transform) in player_qy.iter() {
             // If the player has a cooldown component and it has a start time and the cooldown is not done, then return and exit the system
             if let Ok(mut shoot_cooldown) = shoot_cooldown_qy.get_mut(entity) {
                 if let Some(cooldown_start_time) = shoot_cooldown.cooldown_start {
-                    if time.elapsed_secs() - cooldown_start_time < shoot_cooldown.cooldown {
+                    // Same fire rate in real time during bullet time
+                    let cooldown = shoot_cooldown.cooldown / bullet_time.player_time_factor();
+                    if time.elapsed_secs() - cooldown_start_time < cooldown {
                         return;
                     } else {
                         shoot_cooldown.cooldown_start = Some(time.elapsed_secs());
@@ -124,17 +286,56 @@ fn player_shoot_event(
             }
 
             let direction = (mouse_world_pos - transform.translation.truncate()).normalize();
-            let damage = PLAYER_PROJECTILE_DAMAGE;
-            spawn_projectile(
-                &mut commands,
-                // vec2(transform.translation.x, transform.translation.y + 100.).extend(0.),
-                transform.translation,
-                direction,
-                PROJECTILE_DEFAULT_VELOCITY,
-                damage,
-                PROJECTILE_DEFAULT_KNOCKBACK,
-                vec![entity],
-            );
+            match shoot_event.weapon {
+                PlayerWeapon::Gun => {
+                    let damage = PLAYER_PROJECTILE_DAMAGE;
+                    let projectile_entity = spawn_projectile(
+                        &mut commands,
+                        // vec2(transform.translation.x, transform.translation.y + 100.).extend(0.),
+                        transform.translation,
+                        direction,
+                        PROJECTILE_DEFAULT_VELOCITY,
+                        damage,
+                        PROJECTILE_DEFAULT_KNOCKBACK,
+                        vec![entity],
+                    );
+                    commands
+                        .entity(projectile_entity)
+                        .insert(InflictsStatusEffect(PLAYER_BULLET_BLEED));
+                    if gun_modifiers.ricochet_bounces > 0 {
+                        commands.entity(projectile_entity).insert(Ricochet {
+                            bounces_left: gun_modifiers.ricochet_bounces,
+                        });
+                    }
+                    if gun_modifiers.pierce_count > 0 {
+                        commands
+                            .entity(projectile_entity)
+                            .insert(Piercing::new(gun_modifiers.pierce_count));
+                    }
+                }
+                PlayerWeapon::RocketLauncher => {
+                    let rocket_entity = spawn_rocket(
+                        &mut commands,
+                        transform.translation,
+                        direction,
+                        PLAYER_ROCKET_EXPLOSIVE,
+                        vec![entity],
+                    );
+                    commands
+                        .entity(rocket_entity)
+                        .insert(InflictsStatusEffect(PLAYER_ROCKET_BURN));
+                }
+                PlayerWeapon::Railgun => {
+                    commands.trigger(HitscanEvent {
+                        shooter: entity,
+                        origin: transform.translation.truncate(),
+                        direction,
+                        max_distance: HITSCAN_MAX_DISTANCE,
+                        damage: PLAYER_RAILGUN_DAMAGE,
+                        knockback: PROJECTILE_DEFAULT_KNOCKBACK,
+                    });
+                }
+            }
         }
     }
 }
@@ -145,6 +346,22 @@ pub struct MovementModifiers {
     movement_force: f32,
     max_running_speed: f32,
     jumping_force: f32,
+    // Fastest the player falls while sliding down a wall
+    wall_slide_speed: f32,
+    // Sideways speed of a wall jump, away from the wall
+    wall_jump_push: f32,
+    // Seconds after walking off a ledge the ground jump can still be used
+    coyote_time: f32,
+    // Seconds a jump pressed in the air is remembered, going off on landing
+    jump_buffer_time: f32,
+    // Fraction of the upward speed kept when letting go of jump early
+    jump_release_velocity_scale: f32,
+    // Fraction of the running speed kept while crouching
+    crouch_speed_scale: f32,
+    // Crouching while running faster than this starts a slide
+    slide_min_speed: f32,
+    // How fast a slide loses its speed
+    slide_deceleration: f32,
 }
 impl Default for MovementModifiers {
     fn default() -> Self {
@@ -152,6 +369,14 @@ impl Default for MovementModifiers {
             movement_force: 4000.,
             max_running_speed: 0.2,
             jumping_force: 0.25,
+            wall_slide_speed: 0.05,
+            wall_jump_push: 0.3,
+            coyote_time: 0.1,
+            jump_buffer_time: 0.15,
+            jump_release_velocity_scale: 0.5,
+            crouch_speed_scale: 0.5,
+            slide_min_speed: 0.15,
+            slide_deceleration: 0.2,
         }
     }
 }
@@ -177,7 +402,7 @@ fn look_in_walk_direction(
 }
 
 fn landed_from_jumping(
-    landed_event: On<LandedEvent>,
+    landed_event: On<Landed>,
     mut jumping_query: Query<(&Jumping, &mut Sprite)>,
     mut commands: Commands,
     animation_image_handles: Res<AnimationImageHandles>,
@@ -189,46 +414,53 @@ fn landed_from_jumping(
     }
 }
 
-#[derive(EntityEvent)]
-struct LandedEvent {
-    entity: Entity,
+fn reset_jumps_on_landing(
+    landed_event: On<Landed>,
+    mut jumps_left_qy: Query<&mut JumpsLeft>,
+    mut commands: Commands,
+) {
+    if let Ok(mut jumps_left) = jumps_left_qy.get_mut(landed_event.entity) {
+        jumps_left.0 = 2;
+        commands.entity(landed_event.entity).remove::<CoyoteTime>();
+    }
 }
 
-fn reset_jumps_on_ground(
-    mut player_qy: Query<
-        (
-            Entity,
-            &Transform,
-            &LinearVelocity,
-            &CollidingEntities,
-            &mut JumpsLeft,
-        ),
-        With<Player>,
-    >,
-    ground_qy: Query<&Transform, With<Ground>>,
+/// Left the ground without jumping, the ground jump is kept for a moment
+#[derive(Component)]
+struct CoyoteTime {
+    left_ground_at: f32,
+}
+
+/// Jump pressed in the air with no jumps left, goes off if the player lands soon after
+#[derive(Component)]
+struct BufferedJump {
+    pressed_at: f32,
+}
+
+fn start_coyote_time(
+    left_ground_event: On<LeftGround>,
+    player_qy: Query<(), (With<Player>, Without<Jumping>)>,
+    time: Res<Time>,
     mut commands: Commands,
 ) {
-    for (entity, player_transform, velocity, colliding_entities, mut jumps_left) in
-        player_qy.iter_mut()
-    {
-        let player_y = player_transform.translation.y;
-        let mut is_grounded = false;
-
-        // Check if the player is colliding with ground and is above it (or at same level falling)
-        for &ground_entity in colliding_entities.iter() {
-            if let Ok(ground_transform) = ground_qy.get(ground_entity) {
-                let ground_y = ground_transform.translation.y;
-                // Only consider grounded if player is at or above the ground and falling/stationary
-                if player_y >= ground_y && velocity.y <= 0.0 {
-                    is_grounded = true;
-                    commands.trigger(LandedEvent { entity });
-                    break;
-                }
-            }
-        }
+    if player_qy.contains(left_ground_event.entity) {
+        commands.entity(left_ground_event.entity).insert(CoyoteTime {
+            left_ground_at: time.elapsed_secs(),
+        });
+    }
+}
 
-        if is_grounded {
-            jumps_left.0 = 2;
+/// Once coyote time runs out only the air jump is left
+fn end_coyote_time(
+    mut coyote_qy: Query<(Entity, &CoyoteTime, &mut JumpsLeft)>,
+    movement_modifiers: Res<MovementModifiers>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, coyote_time, mut jumps_left) in coyote_qy.iter_mut() {
+        if time.elapsed_secs() - coyote_time.left_ground_at > movement_modifiers.coyote_time {
+            jumps_left.0 = jumps_left.0.min(1);
+            commands.entity(entity).remove::<CoyoteTime>();
         }
     }
 }
@@ -291,6 +523,8 @@ fn _in(min: f32, check: f32, max: f32) -> bool {
 #[derive(Component)]
 struct Jumping {
     start_time: f32,
+    // Whether the jump key has been let go since, the jump is only cut short once
+    jump_released: bool,
 }
 
 #[derive(EntityEvent)]
@@ -303,38 +537,236 @@ fn start_jump_animation(jump_event: On<JumpEvent>, mut commands: Commands) {
     commands.entity(jump_event.entity).insert(
         (Jumping {
             start_time: jump_event.start_time,
+            jump_released: false,
         }),
     );
 }
 
-#[derive(Component)]
-pub struct Ground;
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+pub enum Ground {
+    Solid,
+    // Only collides with what's on top of it, see `platforms`
+    OneWay,
+}
 
 #[derive(Component)]
 pub struct JumpsLeft(pub i8);
 
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum WallSide {
+    Left,
+    Right,
+}
+
+impl WallSide {
+    /// Direction pointing from the entity into the wall
+    fn direction(self) -> Dir2 {
+        match self {
+            WallSide::Left => Dir2::NEG_X,
+            WallSide::Right => Dir2::X,
+        }
+    }
+}
+
+/// In the air right next to a wall
+#[derive(Component)]
+pub struct TouchingWall(pub WallSide);
+
+// How far to the side a wall is looked for
+const WALL_DETECTION_DISTANCE: f32 = 2.;
+// The probe is this much narrower on each side, so a wall the player is pressed against still
+// counts as a hit instead of as already overlapping
+const WALL_DETECTION_SKIN: f32 = 1.;
+// Fraction of the player's height used to look for walls, so floors and ceilings aren't walls
+const WALL_DETECTION_HEIGHT_FRACTION: f32 = 0.8;
+
+fn detect_walls(
+    player_qy: Query<
+        (
+            Entity,
+            &Transform,
+            &Collider,
+            Has<Grounded>,
+            Has<TouchingWall>,
+        ),
+        With<Player>,
+    >,
+    ground_qy: Query<&Ground>,
+    spatial_query: SpatialQuery,
+    mut commands: Commands,
+) {
+    for (entity, transform, collider, is_grounded, was_touching_wall) in player_qy.iter() {
+        let player_pos = transform.translation.truncate();
+        let wall_side = if is_grounded {
+            None
+        } else {
+            let size = collider.aabb(player_pos, 0.).size();
+            let probe = Collider::rectangle(
+                size.x - 2. * WALL_DETECTION_SKIN,
+                size.y * WALL_DETECTION_HEIGHT_FRACTION,
+            );
+            let filter =
+                SpatialQueryFilter::from_excluded_entities([entity]).with_mask(GameLayer::Default);
+            [WallSide::Left, WallSide::Right].into_iter().find(|side| {
+                spatial_query
+                    .cast_shape(
+                        &probe,
+                        player_pos,
+                        0.,
+                        side.direction(),
+                        &ShapeCastConfig::from_max_distance(
+                            WALL_DETECTION_SKIN + WALL_DETECTION_DISTANCE,
+                        ),
+                        &filter,
+                    )
+                    // The sides of one-way platforms aren't walls
+                    .is_some_and(|hit| matches!(ground_qy.get(hit.entity), Ok(Ground::Solid)))
+            })
+        };
+        match wall_side {
+            Some(side) => {
+                commands.entity(entity).insert(TouchingWall(side));
+            }
+            None if was_touching_wall => {
+                commands.entity(entity).remove::<TouchingWall>();
+            }
+            None => {}
+        }
+    }
+}
+
+/// Falling slowly while against a wall
+fn wall_slide(
+    mut player_qy: Query<
+        &mut LinearVelocity,
+        (
+            With<Player>,
+            With<TouchingWall>,
+            Without<Dashing>,
+            Without<GroundPounding>,
+        ),
+    >,
+    movement_modifiers: Res<MovementModifiers>,
+    bullet_time: Res<BulletTime>,
+) {
+    let max_fall_speed = movement_modifiers.movement_force
+        * movement_modifiers.wall_slide_speed
+        * bullet_time.player_time_factor();
+    for mut velocity in player_qy.iter_mut() {
+        velocity.y = velocity.y.max(-max_fall_speed);
+    }
+}
+
 #[derive(Component)]
 pub struct Player;
 
 pub fn player_movement(
     keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut player_info: Query<(Entity, &mut LinearVelocity, &mut JumpsLeft), With<Player>>,
+    mut player_info: Query<
+        (
+            Entity,
+            &mut LinearVelocity,
+            &mut JumpsLeft,
+            Option<&StatusEffects>,
+            Option<&TouchingWall>,
+            Option<&BufferedJump>,
+            Option<&mut Jumping>,
+            Option<&Grounded>,
+            Has<Crouching>,
+            Has<Sliding>,
+            Has<Swinging>,
+        ),
+        (
+            With<Player>,
+            Without<Stunned>,
+            Without<Hanging>,
+            Without<ClimbingUp>,
+        ),
+    >,
+    ground_qy: Query<&Ground>,
     time: Res<Time>,
     movement_modifiers: Res<MovementModifiers>,
+    bullet_time: Res<BulletTime>,
     mut commands: Commands,
 ) {
-    for (entity, mut rb_vels, mut jumps_left) in player_info.iter_mut() {
-        let max_running_speed =
-            movement_modifiers.movement_force * movement_modifiers.max_running_speed;
-
-        if keyboard_input.any_just_pressed([KeyCode::KeyW, KeyCode::ArrowUp]) && jumps_left.0 > 0 {
-            rb_vels.y = movement_modifiers.movement_force * movement_modifiers.jumping_force;
-            jumps_left.0 -= 1;
-            // Trigger JumpEvent
-            commands.trigger(JumpEvent {
-                entity,
-                start_time: time.elapsed_secs(),
-            });
+    // The player keeps moving at normal speed while the world is slowed down
+    let time_factor = bullet_time.player_time_factor();
+    for (
+        entity,
+        mut rb_vels,
+        mut jumps_left,
+        status_effects_opt,
+        touching_wall_opt,
+        buffered_jump_opt,
+        jumping_opt,
+        grounded_opt,
+        is_crouching,
+        is_sliding,
+        is_swinging,
+    ) in player_info.iter_mut()
+    {
+        // Slows scale the movement modifiers for this entity
+        let movement_force = movement_modifiers.movement_force
+            * status_effects_opt.map_or(1., |effects| effects.movement_scale());
+        let mut max_running_speed = movement_force * movement_modifiers.max_running_speed;
+        if is_crouching {
+            max_running_speed *= movement_modifiers.crouch_speed_scale;
+        }
+        let on_one_way_platform = grounded_opt.is_some_and(|grounded| {
+            matches!(ground_qy.get(grounded.ground), Ok(Ground::OneWay))
+        });
+
+        let jump_pressed = keyboard_input.any_just_pressed([KeyCode::KeyW, KeyCode::ArrowUp]);
+        // A jump pressed shortly before landing goes off once there are jumps again
+        let jump_buffered = buffered_jump_opt.is_some_and(|buffered_jump| {
+            time.elapsed_secs() - buffered_jump.pressed_at <= movement_modifiers.jump_buffer_time
+        });
+        // While swinging the jump keys reel in the rope instead
+        if (jump_pressed || jump_buffered) && !is_swinging {
+            if keyboard_input.any_pressed(CROUCH_KEYS) && on_one_way_platform {
+                // Down and jump drops through the platform instead of jumping
+                commands.entity(entity).remove::<BufferedJump>().insert(DroppingThrough {
+                    until: time.elapsed_secs() + DROP_THROUGH_TIME,
+                });
+            } else if let Some(touching_wall) = touching_wall_opt {
+                // Kick off away from the wall, keeping the air jump
+                let away_from_wall = -touching_wall.0.direction();
+                rb_vels.0 = vec2(
+                    away_from_wall.x * movement_force * movement_modifiers.wall_jump_push,
+                    movement_force * movement_modifiers.jumping_force,
+                ) * time_factor;
+                jumps_left.0 = 1;
+                commands.entity(entity).remove::<BufferedJump>();
+                commands.trigger(JumpEvent {
+                    entity,
+                    start_time: time.elapsed_secs(),
+                });
+            } else if jumps_left.0 > 0 {
+                rb_vels.y = movement_force * movement_modifiers.jumping_force * time_factor;
+                jumps_left.0 -= 1;
+                commands
+                    .entity(entity)
+                    .remove::<(BufferedJump, CoyoteTime)>();
+                // Trigger JumpEvent
+                commands.trigger(JumpEvent {
+                    entity,
+                    start_time: time.elapsed_secs(),
+                });
+            } else if jump_pressed {
+                commands.entity(entity).insert(BufferedJump {
+                    pressed_at: time.elapsed_secs(),
+                });
+            }
+        }
+
+        // Letting go of jump early cuts the jump short
+        if keyboard_input.any_just_released([KeyCode::KeyW, KeyCode::ArrowUp]) {
+            if let Some(mut jumping) = jumping_opt {
+                if !jumping.jump_released && rb_vels.y > 0. {
+                    rb_vels.y *= movement_modifiers.jump_release_velocity_scale;
+                }
+                jumping.jump_released = true;
+            }
         }
 
         let left = keyboard_input.any_pressed([KeyCode::KeyA, KeyCode::ArrowLeft]);
@@ -350,18 +782,390 @@ pub fn player_movement(
 
         let x_axis_movement = (-(left as i8) + right as i8) as f32;
         let horizontal_velocity_delta_from_movement =
-            x_axis_movement * movement_modifiers.movement_force * time.delta_secs();
+            x_axis_movement * movement_force * time_factor * time_factor * time.delta_secs();
 
         let horizontal_velocity = rb_vels.x;
 
-        if (horizontal_velocity + horizontal_velocity_delta_from_movement).abs()
-            <= max_running_speed
+        // Slides keep going on their own momentum
+        if !is_sliding
+            && (horizontal_velocity + horizontal_velocity_delta_from_movement).abs()
+                <= max_running_speed * time_factor
         {
             rb_vels.x += horizontal_velocity_delta_from_movement;
         }
     }
 }
 
+const CROUCH_KEYS: [KeyCode; 2] = [KeyCode::KeyS, KeyCode::ArrowDown];
+// Height of the crouching collider relative to the standing one
+const CROUCH_HEIGHT_FRACTION: f32 = 0.5;
+// Shrinks the standing up check, so the floor the player is crouching on doesn't block it
+const STAND_UP_SKIN: f32 = 1.;
+
+/// Crouched down with a shorter collider
+#[derive(Component)]
+pub struct Crouching {
+    standing_size: Vec2,
+}
+
+/// Sliding along the ground on the speed from running, losing it over time
+#[derive(Component)]
+pub struct Sliding {
+    speed: f32,
+}
+
+fn player_crouch(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut player_qy: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Collider,
+            &mut Sprite,
+            &LinearVelocity,
+            Option<&Crouching>,
+            Has<Grounded>,
+        ),
+        With<Player>,
+    >,
+    mut hitbox_qy: Query<(&Hitbox, &mut Collider, &mut Transform), Without<Player>>,
+    ground_qy: Query<(), With<Ground>>,
+    spatial_query: SpatialQuery,
+    movement_modifiers: Res<MovementModifiers>,
+    bullet_time: Res<BulletTime>,
+    mut commands: Commands,
+) {
+    let wants_to_crouch = keyboard_input.any_pressed(CROUCH_KEYS);
+    for (
+        entity,
+        mut transform,
+        mut collider,
+        mut sprite,
+        velocity,
+        crouching_opt,
+        is_grounded,
+    ) in player_qy.iter_mut()
+    {
+        let current_size = collider.aabb(Vec2::ZERO, 0.).size();
+        match crouching_opt {
+            None if wants_to_crouch && is_grounded => {
+                let crouching_size = vec2(current_size.x, current_size.y * CROUCH_HEIGHT_FRACTION);
+                *collider = Collider::rectangle(crouching_size.x, crouching_size.y);
+                sprite.custom_size = Some(crouching_size);
+                resize_hitboxes(entity, &mut hitbox_qy, crouching_size);
+                // Keep the feet where they were
+                transform.translation.y -= (current_size.y - crouching_size.y) / 2.;
+                commands.entity(entity).insert(Crouching {
+                    standing_size: current_size,
+                });
+                let slide_min_speed = movement_modifiers.movement_force
+                    * movement_modifiers.slide_min_speed
+                    * bullet_time.player_time_factor();
+                if velocity.x.abs() >= slide_min_speed {
+                    commands
+                        .entity(entity)
+                        .insert(Sliding { speed: velocity.x });
+                }
+            }
+            Some(crouching) if !(wants_to_crouch && is_grounded) => {
+                let standing_size = crouching.standing_size;
+                let standing_pos = transform.translation.truncate()
+                    + Vec2::Y * (standing_size.y - current_size.y) / 2.;
+                // Stay crouched while there is no room to stand up
+                let filter = SpatialQueryFilter::from_excluded_entities([entity])
+                    .with_mask(GameLayer::Default);
+                let is_blocked = spatial_query
+                    .shape_intersections(
+                        &Collider::rectangle(
+                            standing_size.x - 2. * STAND_UP_SKIN,
+                            standing_size.y - 2. * STAND_UP_SKIN,
+                        ),
+                        standing_pos,
+                        0.,
+                        &filter,
+                    )
+                    .into_iter()
+                    .any(|hit_entity| ground_qy.contains(hit_entity));
+                if is_blocked {
+                    continue;
+                }
+                *collider = Collider::rectangle(standing_size.x, standing_size.y);
+                sprite.custom_size = Some(standing_size);
+                resize_hitboxes(entity, &mut hitbox_qy, standing_size);
+                transform.translation.y = standing_pos.y;
+                commands.entity(entity).remove::<(Crouching, Sliding)>();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Fits the head and body hitboxes of `owner` to a character of `size`
+fn resize_hitboxes(
+    owner: Entity,
+    hitbox_qy: &mut Query<(&Hitbox, &mut Collider, &mut Transform), Without<Player>>,
+    size: Vec2,
+) {
+    for (hitbox, mut collider, mut transform) in hitbox_qy.iter_mut() {
+        if hitbox.owner == owner {
+            (*collider, *transform) = hitbox_shape(hitbox.part, size);
+        }
+    }
+}
+
+fn player_slide(
+    mut sliding_qy: Query<(Entity, &mut Sliding, &mut LinearVelocity), With<Player>>,
+    movement_modifiers: Res<MovementModifiers>,
+    bullet_time: Res<BulletTime>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    let time_factor = bullet_time.player_time_factor();
+    let deceleration =
+        movement_modifiers.movement_force * movement_modifiers.slide_deceleration * time_factor;
+    // The slide turns into crouch walking once it's down to crouch walking speed
+    let crouch_speed = movement_modifiers.movement_force
+        * movement_modifiers.max_running_speed
+        * movement_modifiers.crouch_speed_scale
+        * time_factor;
+    for (entity, mut sliding, mut velocity) in sliding_qy.iter_mut() {
+        let speed = (sliding.speed.abs() - deceleration * time_factor * time.delta_secs()).max(0.);
+        sliding.speed = speed.copysign(sliding.speed);
+        velocity.x = sliding.speed;
+        if speed <= crouch_speed {
+            commands.entity(entity).remove::<Sliding>();
+        }
+    }
+}
+
+// How far the player's side can be from a ledge corner and still grab it
+const LEDGE_GRAB_REACH: f32 = 8.;
+// How far below the top of the player a ledge corner can be and still be grabbed
+const LEDGE_GRAB_HEIGHT: f32 = 30.;
+// Seconds after dropping from a ledge before ledges can be grabbed again
+const LEDGE_REGRAB_DELAY: f32 = 0.3;
+const CLIMB_UP_DURATION: f32 = 0.25;
+// Gap left between the player and the ground after climbing up, so it doesn't start inside it
+const CLIMB_UP_SKIN: f32 = 1.;
+
+/// Hanging from the corner of a platform, waiting to climb up or drop
+#[derive(Component)]
+pub struct Hanging {
+    // Where the player ends up standing after climbing up
+    climb_to: Vec2,
+    // Gravity scale to restore when letting go, None if there was no GravityScale
+    previous_gravity_scale: Option<f32>,
+}
+
+/// Pulling up onto a ledge from `from` to `to`
+#[derive(Component)]
+pub struct ClimbingUp {
+    from: Vec2,
+    to: Vec2,
+    start_time: f32,
+    previous_gravity_scale: Option<f32>,
+}
+
+/// Let go of a ledge at `time`, so it isn't grabbed again right away
+#[derive(Component)]
+struct DroppedFromLedge {
+    time: f32,
+}
+
+fn restore_gravity_scale(commands: &mut Commands, entity: Entity, previous: Option<f32>) {
+    match previous {
+        Some(previous_gravity_scale) => {
+            commands
+                .entity(entity)
+                .insert(GravityScale(previous_gravity_scale));
+        }
+        None => {
+            commands.entity(entity).remove::<GravityScale>();
+        }
+    }
+}
+
+/// Catches the corner of a platform when the player's upper body falls past it
+fn grab_ledges(
+    mut player_qy: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut LinearVelocity,
+            &mut Sprite,
+            &mut JumpsLeft,
+            &ColliderAabb,
+            Option<&GravityScale>,
+            Option<&DroppedFromLedge>,
+        ),
+        (
+            With<Player>,
+            Without<Grounded>,
+            Without<Hanging>,
+            Without<ClimbingUp>,
+            Without<Swinging>,
+            Without<Dashing>,
+            Without<GroundPounding>,
+        ),
+    >,
+    ground_qy: Query<(&ColliderAabb, &RigidBody), With<Ground>>,
+    time: Res<Time>,
+    animation_image_handles: Res<AnimationImageHandles>,
+    mut commands: Commands,
+) {
+    for (
+        entity,
+        mut transform,
+        mut velocity,
+        mut sprite,
+        mut jumps_left,
+        player_aabb,
+        gravity_scale_opt,
+        dropped_from_ledge_opt,
+    ) in player_qy.iter_mut()
+    {
+        if velocity.y >= 0. {
+            continue;
+        }
+        if let Some(dropped_from_ledge) = dropped_from_ledge_opt {
+            if time.elapsed_secs() - dropped_from_ledge.time < LEDGE_REGRAB_DELAY {
+                continue;
+            }
+            commands.entity(entity).remove::<DroppedFromLedge>();
+        }
+        let half_size = player_aabb.size() / 2.;
+        // Top corners of static platforms, paired with the side of the player they're on
+        let ledge = ground_qy
+            .iter()
+            .filter(|(_, rigid_body)| rigid_body.is_static())
+            .flat_map(|(ground_aabb, _)| {
+                [
+                    (vec2(ground_aabb.min.x, ground_aabb.max.y), WallSide::Right),
+                    (vec2(ground_aabb.max.x, ground_aabb.max.y), WallSide::Left),
+                ]
+            })
+            .find(|(corner, side)| {
+                let player_side_x = match side {
+                    WallSide::Right => player_aabb.max.x,
+                    WallSide::Left => player_aabb.min.x,
+                };
+                (player_side_x - corner.x).abs() <= LEDGE_GRAB_REACH
+                    && corner.y <= player_aabb.max.y
+                    && corner.y >= player_aabb.max.y - LEDGE_GRAB_HEIGHT
+            });
+        let Some((corner, side)) = ledge else {
+            continue;
+        };
+        let into_ledge = side.direction().x;
+        // Hands on the corner, body against the side of the platform
+        transform.translation.x = corner.x - into_ledge * half_size.x;
+        transform.translation.y = corner.y - half_size.y;
+        velocity.0 = Vec2::ZERO;
+        jumps_left.0 = 1;
+        sprite.image = animation_image_handles.player_jump
+            [animation_image_handles.player_jump.len() - 1]
+            .clone();
+        commands
+            .entity(entity)
+            .remove::<(Jumping, Walking, BufferedJump)>()
+            .insert((
+                Hanging {
+                    climb_to: vec2(
+                        corner.x + into_ledge * (half_size.x + CLIMB_UP_SKIN),
+                        corner.y + half_size.y + CLIMB_UP_SKIN,
+                    ),
+                    previous_gravity_scale: gravity_scale_opt
+                        .map(|gravity_scale| gravity_scale.0),
+                },
+                GravityScale(0.),
+            ));
+    }
+}
+
+/// Jump climbs up onto the ledge if there is room, down lets go of it
+fn ledge_hang_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut hanging_qy: Query<
+        (Entity, &Hanging, &Transform, &Collider, &mut LinearVelocity),
+        With<Player>,
+    >,
+    ground_qy: Query<(), With<Ground>>,
+    spatial_query: SpatialQuery,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, hanging, transform, collider, mut velocity) in hanging_qy.iter_mut() {
+        // Stay put on the ledge
+        velocity.0 = Vec2::ZERO;
+        if keyboard_input.any_just_pressed(CROUCH_KEYS) {
+            restore_gravity_scale(&mut commands, entity, hanging.previous_gravity_scale);
+            commands
+                .entity(entity)
+                .remove::<Hanging>()
+                .insert(DroppedFromLedge {
+                    time: time.elapsed_secs(),
+                });
+        } else if keyboard_input.any_just_pressed([KeyCode::KeyW, KeyCode::ArrowUp]) {
+            let filter =
+                SpatialQueryFilter::from_excluded_entities([entity]).with_mask(GameLayer::Default);
+            let is_blocked = spatial_query
+                .shape_intersections(collider, hanging.climb_to, 0., &filter)
+                .into_iter()
+                .any(|hit_entity| ground_qy.contains(hit_entity));
+            if is_blocked {
+                continue;
+            }
+            commands.entity(entity).remove::<Hanging>().insert(ClimbingUp {
+                from: transform.translation.truncate(),
+                to: hanging.climb_to,
+                start_time: time.elapsed_secs(),
+                previous_gravity_scale: hanging.previous_gravity_scale,
+            });
+        }
+    }
+}
+
+/// Moves the player up and then over the ledge, playing the jump frames backwards
+fn climb_up(
+    mut climbing_qy: Query<(
+        Entity,
+        &ClimbingUp,
+        &mut Transform,
+        &mut LinearVelocity,
+        &mut Sprite,
+    )>,
+    bullet_time: Res<BulletTime>,
+    time: Res<Time>,
+    animation_image_handles: Res<AnimationImageHandles>,
+    mut commands: Commands,
+) {
+    // Same climb in real time during bullet time
+    let duration = CLIMB_UP_DURATION / bullet_time.player_time_factor();
+    for (entity, climbing_up, mut transform, mut velocity, mut sprite) in climbing_qy.iter_mut()
+    {
+        velocity.0 = Vec2::ZERO;
+        let progress = ((time.elapsed_secs() - climbing_up.start_time) / duration).min(1.);
+        // Up beside the platform first, then over onto it
+        let above_ledge = vec2(climbing_up.from.x, climbing_up.to.y);
+        let position = if progress < 0.5 {
+            climbing_up.from.lerp(above_ledge, progress * 2.)
+        } else {
+            above_ledge.lerp(climbing_up.to, progress * 2. - 1.)
+        };
+        transform.translation = position.extend(transform.translation.z);
+
+        let frames = &animation_image_handles.player_jump;
+        let frame = ((1. - progress) * (frames.len() - 1) as f32).round() as usize;
+        sprite.image = frames[frame].clone();
+
+        if progress >= 1. {
+            sprite.image = animation_image_handles.player_default.clone();
+            restore_gravity_scale(&mut commands, entity, climbing_up.previous_gravity_scale);
+            commands.entity(entity).remove::<ClimbingUp>();
+        }
+    }
+}
+
 #[derive(Component)]
 struct Walking {
     start_time: f32,
@@ -407,7 +1211,8 @@ fn walking_animation(
 fn camera_movement(
     mut qy_camera_transform: Query<&mut Transform, (With<Camera2d>, Without<Player>)>,
     player_transform_query: Query<&Transform, (With<Player>, Without<Camera2d>)>,
-    time_res: Res<Time>,
+    // Real time so the camera keeps up during bullet time
+    time_res: Res<Time<Real>>,
 ) {
     for mut camera_transform in &mut qy_camera_transform {
         for player_transform in player_transform_query {
@@ -419,113 +1224,108 @@ fn camera_movement(
     }
 }
 
-#[derive(Resource, Default)]
-struct RightClickStartPostion(Option<Vec2>);
+const DASH_DRAG_COLOR: Color = Color::srgb(0.9, 0.9, 0.9);
+const DASH_PATH_COLOR: Color = Color::srgb(0.3, 0.8, 1.0);
+const DASH_BLOCKED_COLOR: Color = Color::srgb(1.0, 0.6, 0.2);
 
-fn right_click_start_position_system(
-    mut right_click_start_position: ResMut<RightClickStartPostion>,
-    mouse_input: Res<ButtonInput<MouseButton>>,
-    windows: Query<&Window>,
+/// While aiming a dash, draws the drag and the path the dash would take, up to where it would hit something
+fn draw_dash_preview(
+    mut gizmos: Gizmos,
+    spatial_query: SpatialQuery,
+    window_qy: Query<&Window>,
+    camera_transform_qy: Query<&Transform, With<Camera>>,
+    player_qy: Query<
+        (Entity, &Transform, &Collider, &AbilityLoadout),
+        (With<Player>, With<CanDash>),
+    >,
+    dash_config: Res<DashConfig>,
 ) {
-    if mouse_input.just_pressed(MouseButton::Right) {
-        let window = windows.single().unwrap();
+    // The dash input is held down while aiming
+    let Some(start_position) = player_qy
+        .iter()
+        .find_map(|(_, _, _, loadout)| loadout.slot(DASH_ABILITY)?.press_window_position)
+    else {
+        return;
+    };
+    let (Ok(window), Ok(camera_transform)) = (window_qy.single(), camera_transform_qy.single())
+    else {
+        return;
+    };
+    let Some(mouse_screen_position) = window.cursor_position() else {
+        return;
+    };
 
-        if let Some(mouse_screen_position) = window.cursor_position() {
-            right_click_start_position.0 = Some(mouse_screen_position);
-        }
+    // Drag vector, drawn where it was dragged on screen
+    gizmos.arrow_2d(
+        window_to_world_position(window, camera_transform, start_position),
+        window_to_world_position(window, camera_transform, mouse_screen_position),
+        DASH_DRAG_COLOR,
+    );
+
+    let Some(direction) = dash_direction_from_drag(start_position, mouse_screen_position) else {
+        return;
+    };
+    let Ok(dash_dir) = Dir2::new(direction) else {
+        return;
+    };
+    let dash_distance = dash_config.speed * dash_config.duration;
+    for (entity, transform, collider, _) in player_qy.iter() {
+        let player_pos = transform.translation.truncate();
+        let filter =
+            SpatialQueryFilter::from_excluded_entities([entity]).with_mask(GameLayer::Default);
+        // Ignore what the player is already touching, like the floor it's standing on
+        let hit = spatial_query.cast_shape(
+            collider,
+            player_pos,
+            0.,
+            dash_dir,
+            &ShapeCastConfig {
+                ignore_origin_penetration: true,
+                ..ShapeCastConfig::from_max_distance(dash_distance)
+            },
+            &filter,
+        );
+        let (stop_distance, color) = match hit {
+            Some(hit_data) => (hit_data.distance, DASH_BLOCKED_COLOR),
+            None => (dash_distance, DASH_PATH_COLOR),
+        };
+        let stop_pos = player_pos + direction * stop_distance;
+        gizmos.line_2d(player_pos, stop_pos, color);
+        let aabb = collider.aabb(stop_pos, 0.);
+        gizmos.rect_2d(Isometry2d::from_translation(stop_pos), aabb.max - aabb.min, color);
     }
 }
 
-fn right_click_end_position_system(
-    mut right_click_start_position: ResMut<RightClickStartPostion>,
-    // mut ev_dash: EventWriter<DashEvent>,
-    mouse_input: Res<ButtonInput<MouseButton>>,
-    windows: Query<&Window>,
-    player_query: Query<Entity, (With<Player>, With<CanDash>)>,
+// Scroll distance in pixels that counts as one scroll wheel notch
+const PIXELS_PER_SCROLL_LINE: f32 = 100.;
+
+fn grapple_reel_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mouse_scroll: Res<AccumulatedMouseScroll>,
+    grappling_hook_config: Res<GrapplingHookConfig>,
+    player_qy: Query<Entity, (With<Swinging>, With<Player>, Without<Stunned>)>,
     time: Res<Time>,
     mut commands: Commands,
 ) {
-    // If the right mouse button was released
-    if mouse_input.just_released(MouseButton::Right) {
-        // Assume we only have one window
-        let window = windows.single().unwrap();
-
-        // If we have a mouse position
-        if let Some(mouse_screen_position) = window.cursor_position() {
-            // If we have a start position and it's different from the end position
-            if let Some(start_position) = right_click_start_position.0 {
-                if start_position != mouse_screen_position {
-                    // Calculate the direction from start to end position
-                    let mut direction = (mouse_screen_position - start_position).normalize();
-
-                    direction.y = -direction.y; // Invert Y axis because window Y cords go downwards
-
-                    // Send a dash for for all players
-                    for player_entity in player_query {
-                        commands.trigger(DashEvent {
-                            entity: player_entity,
-                            direction: direction,
-                            duration: 0.15,
-                            speed: 4000.,
-                            start_time: time.elapsed_secs(),
-                        });
-                    }
-                }
-            }
-            // Clear the start position
-            right_click_start_position.0 = None;
-        }
-    }
-}
+    let reel_in = keyboard_input.any_pressed([KeyCode::KeyW, KeyCode::ArrowUp]);
+    let reel_out = keyboard_input.any_pressed([KeyCode::KeyS, KeyCode::ArrowDown]);
+    let key_reel_direction = (-(reel_in as i8) + reel_out as i8) as f32;
 
-fn grapple_input_system(
-    input: Res<ButtonInput<KeyCode>>,
-    grapple_keybind: Res<GrappleKeybind>,
-    mut commands: Commands,
-    player_qy: Query<Entity, (With<CanGrapple>, With<Player>)>,
-    window_qy: Query<&Window>,
-    camera_transform_qy: Query<&Transform, With<Camera>>,
-) {
-    if input.just_pressed(grapple_keybind.0) {
-        // get window
-        let window = window_qy
-            .single()
-            .expect("Multiple Windows present, not compatible with current grapple implementation");
-        // try to get raw mouse window position
-        if let Some(mouse_window_pos) = window.cursor_position() {
-            // invert y
-            let mouse_window_pos = vec2(mouse_window_pos.x, window.height() - mouse_window_pos.y);
-            // get camera transform
-            let camera_transform = camera_transform_qy
-                .single()
-                .expect("Found multiple cameras, incompatible with current grapple implementation");
-
-            // Convert camera position to Vec2 using truncate()
-            let camera_pos = camera_transform.translation.truncate();
-
-            // Calculate mouse world position (accounting for centered origin)
-            let window_size = Vec2::new(window.width(), window.height());
-            let mouse_world_pos = mouse_window_pos - window_size / 2.0 + camera_pos;
-
-            for entity in player_qy.iter() {
-                commands.trigger(StartGrapple {
-                    entity,
-                    grapple_world_target: mouse_world_pos,
-                });
-            }
-        }
-    }
-}
+    let scroll_notches = match mouse_scroll.unit {
+        MouseScrollUnit::Line => mouse_scroll.delta.y,
+        MouseScrollUnit::Pixel => mouse_scroll.delta.y / PIXELS_PER_SCROLL_LINE,
+    };
 
-fn end_grapple_input(
-    input: Res<ButtonInput<KeyCode>>,
-    grapple_keybind: Res<GrappleKeybind>,
-    player_qy: Query<Entity, (With<Grappling>, With<Player>)>,
-    mut commands: Commands,
-) {
-    if input.just_released(grapple_keybind.0) {
-        for player in player_qy {
-            commands.trigger(EndGrapple { entity: player });
-        }
+    // Scrolling up reels in
+    let length_delta = key_reel_direction * grappling_hook_config.reel_speed * time.delta_secs()
+        - scroll_notches * grappling_hook_config.scroll_reel_step;
+    if length_delta == 0. {
+        return;
+    }
+    for entity in player_qy.iter() {
+        commands.trigger(ReelRope {
+            entity,
+            length_delta,
+        });
     }
 }
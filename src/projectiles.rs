@@ -1,4 +1,7 @@
 use crate::game_data::*;
+use crate::player::Ground;
+use crate::props::Prop;
+use crate::status_effects::*;
 use avian2d::prelude::*;
 use bevy::prelude::*;
 use std::collections::HashSet;
@@ -8,9 +11,19 @@ pub struct ProjectilesPlugin;
 impl Plugin for ProjectilesPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(FixedLast, spawn_collisions_continious)
+            .add_systems(
+                FixedUpdate,
+                explode_on_fuse.run_if(in_state(GameState::PlayingLevel)),
+            )
+            .add_systems(
+                Update,
+                (draw_explosion_flashes, draw_tracers).run_if(in_state(GameState::PlayingLevel)),
+            )
             .add_observer(spawn_collisions)
             .add_observer(projectile_collision)
-            .add_observer(projectile_hit_event);
+            .add_observer(projectile_hit_event)
+            .add_observer(explosion_event)
+            .add_observer(hitscan_event);
     }
 }
 
@@ -23,9 +36,72 @@ struct Projectile {
 #[derive(Component)]
 struct ProjectileMarkedForDespawn;
 
+/// Makes a projectile explode instead of dealing a direct hit. The explosion damages and knocks
+/// back every `CanBeHitByProjectile` body inside `radius`, falling off linearly towards the edge.
+#[derive(Component, Clone, Copy)]
+pub struct Explosive {
+    pub radius: f32,
+    pub damage: f32,
+    pub knockback: f32,
+    pub explode_on_impact: bool,
+}
+
+/// Lets a projectile bounce off `Ground` instead of despawning
+#[derive(Component)]
+pub struct Ricochet {
+    pub bounces_left: u8,
+}
+
+/// Lets a projectile pass through characters instead of despawning on the first hit
+#[derive(Component)]
+pub struct Piercing {
+    pub hits_left: u8,
+    // Characters already hit, so passing through both the head and body only counts once
+    already_hit: Vec<Entity>,
+}
+impl Piercing {
+    pub fn new(hits_left: u8) -> Self {
+        Piercing {
+            hits_left,
+            already_hit: Vec::new(),
+        }
+    }
+}
+
+/// Sends projectiles that touch it back the way they came instead of being hit
+#[derive(Component)]
+pub struct ReflectsProjectiles;
+
+/// A projectile was sent back by `entity`, which now owns it
+#[derive(EntityEvent)]
+pub struct ProjectileReflected {
+    pub entity: Entity,
+    pub projectile: Entity,
+}
+
+/// Time (elapsed secs) at which an explosive projectile detonates by itself
+#[derive(Component)]
+struct ExplosiveFuse {
+    explode_at: f32,
+}
+
+pub const ROCKET_VELOCITY: f32 = 800.;
+pub const GRENADE_VELOCITY: f32 = 900.;
+pub const GRENADE_FUSE: f32 = 2.5;
+const GRENADE_GRAVITY_SCALE: f32 = 0.3;
+const EXPLOSION_FLASH_DURATION: f32 = 0.2;
+pub const HITSCAN_MAX_DISTANCE: f32 = 3000.;
+const TRACER_DURATION: f32 = 0.3;
+// Speed of a reflected projectile relative to how fast it came in
+const REFLECTED_PROJECTILE_SPEEDUP: f32 = 1.5;
+
 #[derive(Component)]
 struct ProjectileDisgardInitialSpawnCollisionWith(Vec<Entity>);
 
+/// Character that fired the projectile, it never hits props attached to them like their shield
+#[derive(Component)]
+pub struct ShotBy(pub Entity);
+
 pub fn spawn_projectile(
     commands: &mut Commands,
     position: Vec3,
@@ -34,7 +110,7 @@ pub fn spawn_projectile(
     damage: f32,
     knockback: f32,
     disgard_initial_collision_with: Vec<Entity>,
-) {
+) -> Entity {
     let projectile_size = 5.;
     commands.spawn((
         // Constant projectile components
@@ -55,7 +131,74 @@ pub fn spawn_projectile(
             custom_size: Some(Vec2::new(projectile_size, projectile_size)),
             ..default()
         },
+    )).id()
+}
+
+/// Rocket that flies in a straight line and explodes on the first thing it hits
+pub fn spawn_rocket(
+    commands: &mut Commands,
+    position: Vec3,
+    direction: Vec2,
+    explosive: Explosive,
+    disgard_initial_collision_with: Vec<Entity>,
+) -> Entity {
+    let rocket_entity = spawn_projectile(
+        commands,
+        position,
+        direction,
+        ROCKET_VELOCITY,
+        0.,
+        0.,
+        disgard_initial_collision_with,
+    );
+    commands.entity(rocket_entity).insert((
+        explosive,
+        Sprite {
+            color: Color::srgb(1.0, 0.5, 0.0),
+            custom_size: Some(Vec2::new(12., 6.)),
+            ..default()
+        },
     ));
+    rocket_entity
+}
+
+/// Grenade that is affected by gravity and explodes when its fuse runs out
+/// (or on impact if the explosive says so)
+pub fn spawn_grenade(
+    commands: &mut Commands,
+    position: Vec3,
+    direction: Vec2,
+    explosive: Explosive,
+    fuse: f32,
+    start_time: f32,
+    disgard_initial_collision_with: Vec<Entity>,
+) -> Entity {
+    let grenade_size = 10.;
+    let grenade_entity = spawn_projectile(
+        commands,
+        position,
+        direction,
+        GRENADE_VELOCITY,
+        0.,
+        0.,
+        disgard_initial_collision_with,
+    );
+    commands.entity(grenade_entity).insert((
+        explosive,
+        ExplosiveFuse {
+            explode_at: start_time + fuse,
+        },
+        // Dynamic so that gravity pulls the grenade into an arc
+        RigidBody::Dynamic,
+        GravityScale(GRENADE_GRAVITY_SCALE),
+        Collider::circle(grenade_size / 2.),
+        Sprite {
+            color: Color::srgb(0.2, 0.6, 0.2),
+            custom_size: Some(Vec2::splat(grenade_size)),
+            ..default()
+        },
+    ));
+    grenade_entity
 }
 
 fn spawn_collisions(
@@ -132,26 +275,56 @@ fn spawn_collisions_continious(
 #[derive(Event)]
 struct ProjectileHitEvent {
     hit_entity: Entity,
-    projectile_entity: Entity,
+    // None for hits that don't come from a projectile body, like hitscan shots
+    projectile_entity: Option<Entity>,
+    // The hitbox that registered the hit, if the hit entity uses hitboxes
+    hitbox: Option<Hitbox>,
     damage: f32,
     knockback_impulse: Vec2,
 }
 
 fn projectile_collision(
     collision_event: On<CollisionStart>,
-    projectile_qy: Query<(&Projectile, &LinearVelocity), Without<ProjectileMarkedForDespawn>>,
+    mut projectile_qy: Query<
+        (
+            &Projectile,
+            &mut LinearVelocity,
+            &Transform,
+            Option<&Explosive>,
+            Option<&mut Ricochet>,
+            Option<&mut Piercing>,
+        ),
+        Without<ProjectileMarkedForDespawn>,
+    >,
     hit_entity_qy: Query<(&CanBeHitByProjectile)>,
+    uses_hitboxes_qy: Query<(), With<UsesHitboxes>>,
+    hitbox_qy: Query<&Hitbox>,
+    ground_aabb_qy: Query<&ColliderAabb, With<Ground>>,
+    character_qy: Query<(), With<GameCharacter>>,
+    reflector_qy: Query<&Transform, With<ReflectsProjectiles>>,
     disgard_initial_collision_qy: Query<&ProjectileDisgardInitialSpawnCollisionWith>,
+    shot_by_qy: Query<&ShotBy>,
+    attached_prop_qy: Query<&ChildOf, With<Prop>>,
     mut commands: Commands,
 ) {
     
     // Get both collider entities from the event
     let projectile_entity = collision_event.collider1;
-    let hit_entity = collision_event.collider2;
+    let hit_collider = collision_event.collider2;
+
+    // Characters with hitboxes register hits through them instead of their own collider
+    if uses_hitboxes_qy.contains(hit_collider) {
+        return;
+    }
+    // If a hitbox was hit, the hit goes to the character owning it
+    let hitbox = hitbox_qy.get(hit_collider).ok().copied();
+    let hit_entity = hitbox.map_or(hit_collider, |hitbox| hitbox.owner);
 
-    if let Ok((projectile, linvel)) = projectile_qy.get(projectile_entity) {
+    if let Ok((projectile, mut linvel, transform, explosive_opt, ricochet_opt, piercing_opt)) =
+        projectile_qy.get_mut(projectile_entity)
+    {
         // If the second entity is in CanBeHitByProjectile
-        if let Ok(_) = hit_entity_qy.get(hit_entity) {
+        if let Ok(_) = hit_entity_qy.get(hit_collider) {
             // If the projectile has the "disgard collision with" component
             // check if the other entity is inside that list and if it is
             // disgard this collision
@@ -161,14 +334,83 @@ fn projectile_collision(
                     return;
                 }
             }
-            // Mark projectile for despawn to prevent multiple hits
-            commands.entity(projectile_entity).queue_silenced(|mut entity: EntityWorldMut| {
-                entity.insert(ProjectileMarkedForDespawn);
-            });
+            if let (Ok(shot_by), Ok(child_of)) = (
+                shot_by_qy.get(projectile_entity),
+                attached_prop_qy.get(hit_collider),
+            ) {
+                if child_of.parent() == shot_by.0 {
+                    return;
+                }
+            }
+            if let Ok(reflector_transform) = reflector_qy.get(hit_entity) {
+                // Only reflect projectiles still heading in, a projectile touching both
+                // hitboxes would otherwise get turned around twice
+                let to_reflector =
+                    (reflector_transform.translation - transform.translation).truncate();
+                if linvel.0.dot(to_reflector) > 0. {
+                    linvel.0 = -linvel.0 * REFLECTED_PROJECTILE_SPEEDUP;
+                    // The reflector takes over the projectile, so it can't hit them anymore
+                    commands.entity(projectile_entity).insert((
+                        ProjectileDisgardInitialSpawnCollisionWith(vec![hit_entity]),
+                        ShotBy(hit_entity),
+                    ));
+                    commands.trigger(ProjectileReflected {
+                        entity: hit_entity,
+                        projectile: projectile_entity,
+                    });
+                }
+                return;
+            }
+            // Explosives don't deal a direct hit, they either explode or keep flying until the fuse runs out
+            if let Some(explosive) = explosive_opt {
+                if explosive.explode_on_impact {
+                    commands.entity(projectile_entity).queue_silenced(|mut entity: EntityWorldMut| {
+                        entity.insert(ProjectileMarkedForDespawn);
+                    });
+                    commands.trigger(ExplosionEvent {
+                        position: transform.translation.truncate(),
+                        explosive: *explosive,
+                        source: projectile_entity,
+                    });
+                }
+                return;
+            }
+            // Bounce off ground if the projectile has bounces left
+            if let (Ok(ground_aabb), Some(mut ricochet)) =
+                (ground_aabb_qy.get(hit_entity), ricochet_opt)
+            {
+                if ricochet.bounces_left > 0 {
+                    ricochet.bounces_left -= 1;
+                    let normal = aabb_contact_normal(ground_aabb, transform.translation.truncate());
+                    linvel.0 = linvel.0.reflect(normal);
+                    return;
+                }
+            }
+            // Pass through characters if the projectile can pierce more of them
+            let mut pierced = false;
+            if character_qy.contains(hit_entity) {
+                if let Some(mut piercing) = piercing_opt {
+                    if piercing.already_hit.contains(&hit_entity) {
+                        return;
+                    }
+                    if piercing.hits_left > 0 {
+                        piercing.hits_left -= 1;
+                        piercing.already_hit.push(hit_entity);
+                        pierced = true;
+                    }
+                }
+            }
+            if !pierced {
+                // Mark projectile for despawn to prevent multiple hits
+                commands.entity(projectile_entity).queue_silenced(|mut entity: EntityWorldMut| {
+                    entity.insert(ProjectileMarkedForDespawn);
+                });
+            }
             
             commands.trigger(ProjectileHitEvent {
                 hit_entity,
-                projectile_entity,
+                projectile_entity: Some(projectile_entity),
+                hitbox,
                 damage: projectile.damage,
                 knockback_impulse: linvel.0.normalize() * projectile.knockback,
             });
@@ -176,30 +418,260 @@ fn projectile_collision(
     }
 }
 
+/// Outward normal of the side of `aabb` that `point` is closest to
+fn aabb_contact_normal(aabb: &ColliderAabb, point: Vec2) -> Vec2 {
+    let distance_to_sides = [
+        (point.x - aabb.min.x, Vec2::NEG_X),
+        (aabb.max.x - point.x, Vec2::X),
+        (point.y - aabb.min.y, Vec2::NEG_Y),
+        (aabb.max.y - point.y, Vec2::Y),
+    ];
+    distance_to_sides
+        .into_iter()
+        .min_by(|(a, _), (b, _)| a.abs().total_cmp(&b.abs()))
+        .map(|(_, normal)| normal)
+        .unwrap_or(Vec2::Y)
+}
+
 fn projectile_hit_event(
     projectile_hit_event: On<ProjectileHitEvent>,
     mut commands: Commands,
     mut hit_entity_qy: Query<(Forces), With<CanBeHitByProjectile>>,
     mut health_qy: Query<&mut Health>,
+    marked_for_despawn_qy: Query<(), With<ProjectileMarkedForDespawn>>,
+    inflicts_status_effect_qy: Query<&InflictsStatusEffect>,
+    invulnerable_qy: Query<(), With<Invulnerable>>,
 ) {
     let hit_entity = projectile_hit_event.hit_entity;
     let projectile_entity = projectile_hit_event.projectile_entity;
-    // Apply knockback to hit entity
-    if let Ok(mut hit_entity_force) = hit_entity_qy.get_mut(hit_entity) {
-        commands.entity(hit_entity).remove::<Sleeping>();
-        hit_entity_force.apply_linear_impulse(projectile_hit_event.knockback_impulse);
+    // Invulnerable entities still stop the projectile, they just aren't affected by it
+    if !invulnerable_qy.contains(hit_entity) {
+        // Apply knockback to hit entity
+        if let Ok(mut hit_entity_force) = hit_entity_qy.get_mut(hit_entity) {
+            commands.entity(hit_entity).remove::<Sleeping>();
+            hit_entity_force.apply_linear_impulse(projectile_hit_event.knockback_impulse);
+        }
+        // Deal damage to hit entity if it has a Health component
+        if let Ok(mut health) = health_qy.get_mut(hit_entity) {
+            let damage_multiplier = projectile_hit_event
+                .hitbox
+                .map_or(1., |hitbox| hitbox.damage_multiplier);
+            health.take_damage(projectile_hit_event.damage * damage_multiplier);
+        }
+        if let Some(inflicts_status_effect) = projectile_entity
+            .and_then(|projectile_entity| inflicts_status_effect_qy.get(projectile_entity).ok())
+        {
+            commands.trigger(ApplyStatusEffect {
+                entity: hit_entity,
+                effect: inflicts_status_effect.0,
+            });
+        }
     }
-    // Deal damage to hit entity if it has a Health component
-    if let Ok(mut health) = health_qy.get_mut(hit_entity) {
-        health.0 -= projectile_hit_event.damage;
-        if health.0 < 0. {
-            health.0 = 0.;
+    // Piercing projectiles keep flying, so only despawn projectiles that are done
+    if let Some(projectile_entity) = projectile_entity {
+        if marked_for_despawn_qy.contains(projectile_entity) {
+            // Use queue_silenced to prevent error if already despawned
+            commands
+                .entity(projectile_entity)
+                .queue_silenced(|mut entity: EntityWorldMut| {
+                    entity.despawn();
+                });
         }
     }
+}
+
+fn explode_on_fuse(
+    fuse_qy: Query<
+        (Entity, &ExplosiveFuse, &Explosive, &Transform),
+        Without<ProjectileMarkedForDespawn>,
+    >,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, fuse, explosive, transform) in fuse_qy.iter() {
+        if time.elapsed_secs() >= fuse.explode_at {
+            commands.entity(entity).insert(ProjectileMarkedForDespawn);
+            commands.trigger(ExplosionEvent {
+                position: transform.translation.truncate(),
+                explosive: *explosive,
+                source: entity,
+            });
+        }
+    }
+}
+
+#[derive(Event)]
+pub struct ExplosionEvent {
+    pub position: Vec2,
+    pub explosive: Explosive,
+    // The projectile that exploded, despawned once the explosion has been applied
+    pub source: Entity,
+}
+
+#[derive(Component)]
+struct ExplosionFlash {
+    position: Vec2,
+    radius: f32,
+    start_time: f32,
+}
+
+fn explosion_event(
+    explosion_event: On<ExplosionEvent>,
+    spatial_query: SpatialQuery,
+    // Explosions hit characters as a whole, not their individual hitboxes
+    hit_entity_qy: Query<&Transform, (With<CanBeHitByProjectile>, Without<Hitbox>)>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    let explosion_pos = explosion_event.position;
+    let explosive = explosion_event.explosive;
+    let filter = SpatialQueryFilter::from_excluded_entities([explosion_event.source]);
+
+    let entities_in_radius = spatial_query.shape_intersections(
+        &Collider::circle(explosive.radius),
+        explosion_pos,
+        0.,
+        &filter,
+    );
+    for hit_entity in entities_in_radius {
+        if let Ok(hit_transform) = hit_entity_qy.get(hit_entity) {
+            let delta = hit_transform.translation.truncate() - explosion_pos;
+            // 1 at the center of the explosion, 0 at the edge
+            let falloff = (1. - delta.length() / explosive.radius).clamp(0., 1.);
+            // Apply damage and knockback through the same path as a direct projectile hit
+            commands.trigger(ProjectileHitEvent {
+                hit_entity,
+                projectile_entity: Some(explosion_event.source),
+                hitbox: None,
+                damage: explosive.damage * falloff,
+                knockback_impulse: delta.normalize_or_zero() * explosive.knockback * falloff,
+            });
+        }
+    }
+
+    commands.spawn((
+        GameEntity::LevelEntity,
+        ExplosionFlash {
+            position: explosion_pos,
+            radius: explosive.radius,
+            start_time: time.elapsed_secs(),
+        },
+    ));
     // Use queue_silenced to prevent error if already despawned
     commands
-        .entity(projectile_entity)
+        .entity(explosion_event.source)
         .queue_silenced(|mut entity: EntityWorldMut| {
             entity.despawn();
         });
 }
+
+fn draw_explosion_flashes(
+    mut gizmos: Gizmos,
+    flash_qy: Query<(Entity, &ExplosionFlash)>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, flash) in flash_qy.iter() {
+        let flash_time = time.elapsed_secs() - flash.start_time;
+        if flash_time > EXPLOSION_FLASH_DURATION {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        // Expand and fade out over the duration of the flash
+        let progress = flash_time / EXPLOSION_FLASH_DURATION;
+        gizmos.circle_2d(
+            flash.position,
+            flash.radius * progress,
+            Color::srgba(1.0, 0.6, 0.1, 1. - progress),
+        );
+    }
+}
+
+/// Instant hit shot along a ray, hits the first `CanBeHitByProjectile` entity in the way
+#[derive(Event)]
+pub struct HitscanEvent {
+    pub shooter: Entity,
+    pub origin: Vec2,
+    pub direction: Vec2,
+    pub max_distance: f32,
+    pub damage: f32,
+    pub knockback: f32,
+}
+
+#[derive(Component)]
+struct Tracer {
+    start: Vec2,
+    end: Vec2,
+    start_time: f32,
+}
+
+fn hitscan_event(
+    hitscan_event: On<HitscanEvent>,
+    spatial_query: SpatialQuery,
+    hit_entity_qy: Query<(), (With<CanBeHitByProjectile>, Without<UsesHitboxes>)>,
+    hitbox_qy: Query<&Hitbox>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    let Ok(direction) = Dir2::new(hitscan_event.direction) else {
+        return;
+    };
+    let origin = hitscan_event.origin;
+    let filter = SpatialQueryFilter::from_excluded_entities([hitscan_event.shooter]);
+
+    let shooter = hitscan_event.shooter;
+    // Only stop at things that can be hit, so the shot flies through sensors like other projectiles.
+    // Characters are hit through their hitboxes, ignoring the shooter's own
+    let hit = spatial_query.cast_ray_predicate(
+        origin,
+        direction,
+        hitscan_event.max_distance,
+        true,
+        &filter,
+        &|entity| {
+            hit_entity_qy.contains(entity)
+                && hitbox_qy.get(entity).map_or(true, |hitbox| hitbox.owner != shooter)
+        },
+    );
+
+    let tracer_end = if let Some(hit_data) = hit {
+        let hitbox = hitbox_qy.get(hit_data.entity).ok().copied();
+        commands.trigger(ProjectileHitEvent {
+            hit_entity: hitbox.map_or(hit_data.entity, |hitbox| hitbox.owner),
+            projectile_entity: None,
+            hitbox,
+            damage: hitscan_event.damage,
+            knockback_impulse: direction.as_vec2() * hitscan_event.knockback,
+        });
+        origin + direction.as_vec2() * hit_data.distance
+    } else {
+        origin + direction.as_vec2() * hitscan_event.max_distance
+    };
+
+    commands.spawn((
+        GameEntity::LevelEntity,
+        Tracer {
+            start: origin,
+            end: tracer_end,
+            start_time: time.elapsed_secs(),
+        },
+    ));
+}
+
+fn draw_tracers(
+    mut gizmos: Gizmos,
+    tracer_qy: Query<(Entity, &Tracer)>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, tracer) in tracer_qy.iter() {
+        let tracer_time = time.elapsed_secs() - tracer.start_time;
+        if tracer_time > TRACER_DURATION {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        // Fade out over the duration of the tracer
+        let alpha = 1. - tracer_time / TRACER_DURATION;
+        gizmos.line_2d(tracer.start, tracer.end, Color::srgba(1.0, 1.0, 0.8, alpha));
+    }
+}
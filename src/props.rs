@@ -0,0 +1,219 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::game_data::*;
+use crate::level::FacingDirection;
+use crate::player::Ground;
+
+const CRATE_MASS: f32 = 200.;
+const SHIELD_MASS: f32 = 100.;
+// Speed a prop flies towards the one yanking it
+const PROP_YANK_SPEED: f32 = 1500.;
+
+pub struct PropsPlugin;
+
+impl Plugin for PropsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(yank_prop).add_systems(
+            FixedUpdate,
+            (move_platforms, keep_shields_in_front).run_if(in_state(GameState::PlayingLevel)),
+        );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PropKind {
+    // Loose dynamic box that can be yanked around
+    Crate,
+    // Static platform that falls down when yanked
+    BreakablePlatform,
+    // Blocks projectiles in front of its owner until it's yanked off
+    Shield,
+    // Kinematic platform moving back and forth, swung from like the world
+    MovingPlatform,
+}
+
+/// Level object the grappling hook can attach to
+#[derive(Component)]
+pub struct Prop(pub PropKind);
+
+#[derive(Component)]
+pub struct MovingPlatform {
+    start: Vec2,
+    end: Vec2,
+    speed: f32,
+    heading_to_end: bool,
+}
+
+#[derive(Component)]
+pub struct Shield {
+    // Distance in front of the owner's center
+    offset: f32,
+}
+
+pub fn spawn_crate(
+    commands: &mut Commands,
+    image: Handle<Image>,
+    position: Vec2,
+    size: f32,
+) -> Entity {
+    commands
+        .spawn((
+            GameEntity::LevelEntity,
+            Prop(PropKind::Crate),
+            // Can be stood on
+            Ground::Solid,
+            CanBeHitByProjectile,
+            Sprite {
+                custom_size: Some(Vec2::splat(size)),
+                image,
+                ..default()
+            },
+            RigidBody::Dynamic,
+            Mass(CRATE_MASS),
+            Transform::from_translation(position.extend(0.)),
+            Collider::rectangle(size, size),
+        ))
+        .id()
+}
+
+pub fn spawn_breakable_platform(
+    commands: &mut Commands,
+    image: Handle<Image>,
+    ground_spawn_data: GroundSpawnData,
+) -> Entity {
+    let platform_entity = spawn_ground(commands, image, ground_spawn_data);
+    // Tinted so it stands out from solid ground
+    commands
+        .entity(platform_entity)
+        .insert(Prop(PropKind::BreakablePlatform))
+        .entry::<Sprite>()
+        .and_modify(|mut sprite| sprite.color = Color::srgb(0.8, 0.6, 0.6));
+    platform_entity
+}
+
+/// Platform moving back and forth between where it is spawned and `travel` away from it
+pub fn spawn_moving_platform(
+    commands: &mut Commands,
+    image: Handle<Image>,
+    ground_spawn_data: GroundSpawnData,
+    travel: Vec2,
+    speed: f32,
+) -> Entity {
+    let start = ground_spawn_data.center();
+    let platform_entity = spawn_ground(commands, image, ground_spawn_data);
+    commands.entity(platform_entity).insert((
+        Prop(PropKind::MovingPlatform),
+        RigidBody::Kinematic,
+        MovingPlatform {
+            start,
+            end: start + travel,
+            speed,
+            heading_to_end: true,
+        },
+    ));
+    platform_entity
+}
+
+/// Shield held in front of `owner`, blocking projectiles coming from that side
+pub fn spawn_shield(commands: &mut Commands, owner: Entity, size: Vec2, offset: f32) -> Entity {
+    commands
+        .spawn((
+            // No GameEntity while attached, it gets despawned together with its owner
+            Prop(PropKind::Shield),
+            Shield { offset },
+            CanBeHitByProjectile,
+            Sprite {
+                color: Color::srgb(0.5, 0.5, 0.6),
+                custom_size: Some(size),
+                ..default()
+            },
+            Transform::from_xyz(offset, 0., 0.1),
+            Collider::rectangle(size.x, size.y),
+            ChildOf(owner),
+        ))
+        .id()
+}
+
+fn move_platforms(
+    mut platform_qy: Query<(&mut MovingPlatform, &Transform, &mut LinearVelocity)>,
+    time: Res<Time>,
+) {
+    for (mut platform, transform, mut velocity) in platform_qy.iter_mut() {
+        let target = if platform.heading_to_end {
+            platform.end
+        } else {
+            platform.start
+        };
+        let to_target = target - transform.translation.truncate();
+        // Turn around once the target would be reached this step
+        if to_target.length() <= platform.speed * time.delta_secs() {
+            platform.heading_to_end = !platform.heading_to_end;
+        }
+        velocity.0 = to_target.normalize_or_zero() * platform.speed;
+    }
+}
+
+fn keep_shields_in_front(
+    mut shield_qy: Query<(&Shield, &ChildOf, &mut Transform)>,
+    facing_qy: Query<&FacingDirection>,
+) {
+    for (shield, child_of, mut transform) in shield_qy.iter_mut() {
+        if let Ok(facing_direction) = facing_qy.get(child_of.parent()) {
+            transform.translation.x = match facing_direction {
+                FacingDirection::Right => shield.offset,
+                FacingDirection::Left => -shield.offset,
+            };
+        }
+    }
+}
+
+/// Pulls a prop loose and sends it flying towards `towards`
+#[derive(EntityEvent)]
+pub struct YankProp {
+    pub entity: Entity,
+    pub towards: Vec2,
+}
+
+fn yank_prop(
+    yank_prop_event: On<YankProp>,
+    mut prop_qy: Query<(&mut Prop, &GlobalTransform)>,
+    mut commands: Commands,
+) {
+    let prop_entity = yank_prop_event.entity;
+    let Ok((mut prop, global_transform)) = prop_qy.get_mut(prop_entity) else {
+        return;
+    };
+    let prop_pos = global_transform.translation().truncate();
+    let yank_velocity =
+        LinearVelocity((yank_prop_event.towards - prop_pos).normalize_or_zero() * PROP_YANK_SPEED);
+
+    match prop.0 {
+        PropKind::Crate => {
+            commands.entity(prop_entity).insert(yank_velocity);
+        }
+        PropKind::BreakablePlatform => {
+            // Breaks loose and becomes a crate-like prop
+            prop.0 = PropKind::Crate;
+            commands
+                .entity(prop_entity)
+                .insert((RigidBody::Dynamic, Mass(CRATE_MASS), yank_velocity));
+        }
+        PropKind::Shield => {
+            // Rip the shield off its owner
+            prop.0 = PropKind::Crate;
+            commands
+                .entity(prop_entity)
+                .remove::<(ChildOf, Shield)>()
+                .insert((
+                    GameEntity::LevelEntity,
+                    Transform::from_translation(global_transform.translation()),
+                    RigidBody::Dynamic,
+                    Mass(SHIELD_MASS),
+                    yank_velocity,
+                ));
+        }
+        // Swung from instead of yanked
+        PropKind::MovingPlatform => {}
+    }
+}
@@ -0,0 +1,166 @@
+use bevy::prelude::*;
+
+use crate::game_data::*;
+
+pub struct StatusEffectsPlugin;
+
+impl Plugin for StatusEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(apply_status_effect).add_systems(
+            FixedUpdate,
+            (
+                remove_expired_status_effects,
+                update_stunned_marker,
+                status_effect_damage_over_time,
+                remove_expired_invulnerability,
+            )
+                .chain()
+                .run_if(in_state(GameState::PlayingLevel)),
+        );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum StatusEffectKind {
+    // Blocks movement, shooting and abilities
+    Stun,
+    // Deals `magnitude` damage per second
+    Burn,
+    // Scales movement by (1 - `magnitude`)
+    Slow,
+    // Deals `magnitude` damage per second
+    Bleed,
+}
+
+#[derive(Clone, Copy)]
+pub struct StatusEffect {
+    pub kind: StatusEffectKind,
+    pub duration: f32,
+    pub magnitude: f32,
+}
+
+#[derive(Clone, Copy)]
+struct ActiveStatusEffect {
+    effect: StatusEffect,
+    start_time: f32,
+}
+
+/// Stack of timed status effects on a character, the same kind can be applied multiple times
+#[derive(Component, Default)]
+pub struct StatusEffects(Vec<ActiveStatusEffect>);
+
+impl StatusEffects {
+    pub fn has(&self, kind: StatusEffectKind) -> bool {
+        self.0.iter().any(|active| active.effect.kind == kind)
+    }
+
+    /// Factor to scale movement by, stacking slows multiply together
+    pub fn movement_scale(&self) -> f32 {
+        self.0
+            .iter()
+            .filter(|active| active.effect.kind == StatusEffectKind::Slow)
+            .map(|active| (1. - active.effect.magnitude).clamp(0., 1.))
+            .product()
+    }
+}
+
+/// Marker kept in sync with `StatusEffects` so systems can filter stunned entities out
+#[derive(Component)]
+pub struct Stunned;
+
+/// Can't be damaged by projectiles until `until`
+#[derive(Component)]
+pub struct Invulnerable {
+    pub until: f32,
+}
+
+/// Status effect a projectile (or its explosion) applies to whatever it hits
+#[derive(Component, Clone, Copy)]
+pub struct InflictsStatusEffect(pub StatusEffect);
+
+#[derive(EntityEvent)]
+pub struct ApplyStatusEffect {
+    pub entity: Entity,
+    pub effect: StatusEffect,
+}
+
+fn apply_status_effect(
+    apply_event: On<ApplyStatusEffect>,
+    character_qy: Query<(), With<GameCharacter>>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    // Only characters can have status effects
+    if !character_qy.contains(apply_event.entity) {
+        return;
+    }
+    let active_effect = ActiveStatusEffect {
+        effect: apply_event.effect,
+        start_time: time.elapsed_secs(),
+    };
+    // Go through commands so effects applied in the same frame stack instead of replacing each other
+    commands
+        .entity(apply_event.entity)
+        .entry::<StatusEffects>()
+        .or_default()
+        .and_modify(move |mut status_effects| status_effects.0.push(active_effect));
+    if apply_event.effect.kind == StatusEffectKind::Stun {
+        commands.entity(apply_event.entity).insert(Stunned);
+    }
+}
+
+fn remove_expired_status_effects(mut status_effects_qy: Query<&mut StatusEffects>, time: Res<Time>) {
+    for mut status_effects in status_effects_qy.iter_mut() {
+        status_effects
+            .0
+            .retain(|active| time.elapsed_secs() - active.start_time < active.effect.duration);
+    }
+}
+
+fn remove_expired_invulnerability(
+    invulnerable_qy: Query<(Entity, &Invulnerable)>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, invulnerable) in invulnerable_qy.iter() {
+        if time.elapsed_secs() >= invulnerable.until {
+            commands.entity(entity).remove::<Invulnerable>();
+        }
+    }
+}
+
+fn update_stunned_marker(
+    status_effects_qy: Query<(Entity, &StatusEffects, Has<Stunned>)>,
+    mut commands: Commands,
+) {
+    for (entity, status_effects, is_stunned) in status_effects_qy.iter() {
+        let should_be_stunned = status_effects.has(StatusEffectKind::Stun);
+        if should_be_stunned && !is_stunned {
+            commands.entity(entity).insert(Stunned);
+        } else if !should_be_stunned && is_stunned {
+            commands.entity(entity).remove::<Stunned>();
+        }
+    }
+}
+
+fn status_effect_damage_over_time(
+    mut status_effects_qy: Query<(&StatusEffects, &mut Health)>,
+    time: Res<Time>,
+) {
+    for (status_effects, mut health) in status_effects_qy.iter_mut() {
+        let damage_per_second: f32 = status_effects
+            .0
+            .iter()
+            .filter(|active| {
+                matches!(
+                    active.effect.kind,
+                    StatusEffectKind::Burn | StatusEffectKind::Bleed
+                )
+            })
+            .map(|active| active.effect.magnitude)
+            .sum();
+        if damage_per_second > 0. {
+            health.take_damage(damage_per_second * time.delta_secs());
+        }
+    }
+}